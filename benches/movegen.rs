@@ -0,0 +1,73 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use chess_rust::game::bitboard::BitBoard;
+use chess_rust::game::board::Board;
+use chess_rust::game::moves::generate_legal_moves;
+
+// Each position is benchmarked under its name, so more can be added by FEN alone
+const POSITIONS: [(&str, &str); 2] = [
+    (
+        "start",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    ),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ),
+];
+
+const PERFT_DEPTH: u32 = 3;
+
+fn bench_legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_legal_moves");
+    for (name, fen) in POSITIONS {
+        let board = Board::from_fen(fen).expect("Benchmark FEN should be valid");
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| generate_legal_moves(black_box(board)))
+        });
+    }
+    group.finish();
+}
+
+// Both backends count the same tree, so each position is timed once per backend
+fn bench_perft(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("perft({PERFT_DEPTH})"));
+    // Perft is slow enough that criterion's default of 100 samples takes minutes
+    group.sample_size(10);
+    for (name, fen) in POSITIONS {
+        let board = Board::from_fen(fen).expect("Benchmark FEN should be valid");
+        group.bench_with_input(BenchmarkId::new("Board", name), &board, |b, board| {
+            b.iter(|| black_box(board).perft(PERFT_DEPTH))
+        });
+
+        let bitboard = BitBoard::from_board(&board).expect("Benchmark FEN should fit a bitboard");
+        group.bench_with_input(
+            BenchmarkId::new("BitBoard", name),
+            &bitboard,
+            |b, bitboard| b.iter(|| black_box(bitboard).perft(PERFT_DEPTH)),
+        );
+    }
+    group.finish();
+}
+
+// Plays and takes back every legal move from the position, as a search does at each node
+fn bench_make_unmake(c: &mut Criterion) {
+    let mut group = c.benchmark_group("make_unmake_move");
+    for (name, fen) in POSITIONS {
+        let mut board = Board::from_fen(fen).expect("Benchmark FEN should be valid");
+        let legal_moves = board.legal_moves();
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                for m in &legal_moves {
+                    let undo = board.make_move(m.clone());
+                    black_box(&board);
+                    board.unmake_move(undo);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_legal_moves, bench_perft, bench_make_unmake);
+criterion_main!(benches);
@@ -0,0 +1,430 @@
+use std::cmp::Reverse;
+
+use crate::game::board::{eval::EvalParams, Board};
+use crate::game::moves::{generate_tactical_moves, Move};
+use crate::game::piece::piece_info::PieceColor;
+
+// Score given to a checkmated side, large enough to outweigh any material difference
+pub const CHECKMATE_SCORE: i32 = 100_000;
+
+#[derive(Clone, Debug)]
+pub struct MoveAnalysis {
+    pub chess_move: Move,
+    pub san: String,
+    pub score: i32,
+    pub gives_check: bool,
+    pub capturing: bool,
+}
+
+/// Statically evaluates the board in centipawns from the perspective of the side to move,
+/// so a positive score means the player about to move is ahead. See `Board::evaluate`.
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_with(board, &EvalParams::default())
+}
+
+/// Evaluates the board like `evaluate`, weighing the material by `params`.
+pub fn evaluate_with(board: &Board, params: &EvalParams) -> i32 {
+    let white_score = board.evaluate_with(params);
+
+    match board.side_to_move() {
+        PieceColor::White => white_score,
+        PieceColor::Black => -white_score,
+    }
+}
+
+/// Searches every line `depth` plies deep and returns the best score the side to move can
+/// force, from its own perspective.
+///
+/// Mates found with more depth remaining are scored further from zero, so the search
+/// prefers the quickest mate and the slowest loss. Positions at the end of the search are
+/// scored by `quiescence`.
+pub fn minimax(board: &Board, depth: u8) -> i32 {
+    minimax_with(board, depth, &EvalParams::default())
+}
+
+/// Searches like `minimax`, scoring positions with `params`.
+pub fn minimax_with(board: &Board, depth: u8, params: &EvalParams) -> i32 {
+    minimax_counted(&mut board.clone(), depth, params, &mut 0)
+}
+
+// Minimax that also counts every position it visits, for comparing against alpha-beta.
+// Like the other searches it walks the tree by making and taking back moves on one board,
+// which it leaves as it found it.
+fn minimax_counted(board: &mut Board, depth: u8, params: &EvalParams, nodes: &mut u64) -> i32 {
+    *nodes += 1;
+    if depth == 0 {
+        return quiescence_counted(board, -i32::MAX, i32::MAX, params, nodes);
+    }
+
+    let legal_moves = board.legal_moves();
+    if legal_moves.is_empty() {
+        if board.is_in_check(board.side_to_move()) {
+            return -(CHECKMATE_SCORE + depth as i32);
+        }
+        // Stalemate
+        return 0;
+    }
+
+    legal_moves
+        .into_iter()
+        .map(|m| {
+            let undo = board.make_move(m);
+            let score = -minimax_counted(board, depth - 1, params, nodes);
+            board.unmake_move(undo);
+            score
+        })
+        .max()
+        .expect("Legal moves were checked to be non-empty")
+}
+
+/// Scores the position like `minimax`, but stops searching a line as soon as it is known
+/// to be no better than `alpha` or at least as good as `beta` for the side to move.
+///
+/// The result is exact when it falls strictly between the bounds, and is clamped to the
+/// nearest bound otherwise. Searching with `-i32::MAX` and `i32::MAX` gives the minimax score.
+pub fn alpha_beta(board: &Board, depth: u8, alpha: i32, beta: i32) -> i32 {
+    alpha_beta_with(board, depth, alpha, beta, &EvalParams::default())
+}
+
+/// Searches like `alpha_beta`, scoring positions with `params`.
+pub fn alpha_beta_with(
+    board: &Board,
+    depth: u8,
+    alpha: i32,
+    beta: i32,
+    params: &EvalParams,
+) -> i32 {
+    alpha_beta_counted(&mut board.clone(), depth, alpha, beta, params, &mut 0)
+}
+
+fn alpha_beta_counted(
+    board: &mut Board,
+    depth: u8,
+    mut alpha: i32,
+    beta: i32,
+    params: &EvalParams,
+    nodes: &mut u64,
+) -> i32 {
+    *nodes += 1;
+    if depth == 0 {
+        return quiescence_counted(board, alpha, beta, params, nodes);
+    }
+
+    let legal_moves = board.legal_moves();
+    if legal_moves.is_empty() {
+        let score = if board.is_in_check(board.side_to_move()) {
+            -(CHECKMATE_SCORE + depth as i32)
+        } else {
+            // Stalemate
+            0
+        };
+        return score.clamp(alpha, beta);
+    }
+
+    for m in legal_moves {
+        let undo = board.make_move(m);
+        let score = -alpha_beta_counted(board, depth - 1, -beta, -alpha, params, nodes);
+        board.unmake_move(undo);
+        if score >= beta {
+            // The opponent already has a better option than letting this line happen
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// Scores the position once it is quiet, by searching only captures and promotions until
+/// none of them improve on standing pat with the static evaluation. This keeps the search
+/// from stopping halfway through an exchange, e.g. after QxP but before PxQ. Captures that
+/// `Board::see` shows losing material are never tried.
+///
+/// Bounds behave as in `alpha_beta`.
+pub fn quiescence(board: &Board, alpha: i32, beta: i32) -> i32 {
+    quiescence_with(board, alpha, beta, &EvalParams::default())
+}
+
+/// Searches like `quiescence`, scoring positions with `params`.
+pub fn quiescence_with(board: &Board, alpha: i32, beta: i32, params: &EvalParams) -> i32 {
+    quiescence_counted(&mut board.clone(), alpha, beta, params, &mut 0)
+}
+
+fn quiescence_counted(
+    board: &mut Board,
+    mut alpha: i32,
+    beta: i32,
+    params: &EvalParams,
+    nodes: &mut u64,
+) -> i32 {
+    // Declining every capture is always an option, so the static score is a lower bound
+    let stand_pat = evaluate_with(board, params);
+    if stand_pat >= beta {
+        return beta;
+    }
+    alpha = alpha.max(stand_pat);
+
+    for m in generate_tactical_moves(board) {
+        // A capture that loses material once the exchange plays out can't beat standing pat
+        if m.promotion.is_none() && board.see(&m) < 0 {
+            continue;
+        }
+        *nodes += 1;
+        let undo = board.make_move(m);
+        let score = -quiescence_counted(board, -beta, -alpha, params, nodes);
+        board.unmake_move(undo);
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// Returns the highest scoring move for the side to move, or None if the game is over.
+///
+/// Uses alpha-beta pruning, so it picks the same move `analyze` ranks first while
+/// searching far fewer positions.
+pub fn best_move(board: &Board, depth: u8) -> Option<Move> {
+    best_move_with(board, depth, &EvalParams::default())
+}
+
+/// Searches for the best move like `best_move`, scoring positions with `params`.
+pub fn best_move_with(board: &Board, depth: u8, params: &EvalParams) -> Option<Move> {
+    best_move_counted(&mut board.clone(), depth, params, &mut 0)
+}
+
+// Ties go to the earliest move in `legal_moves` order, as with the stable sort in `analyze`
+fn best_move_counted(
+    board: &mut Board,
+    depth: u8,
+    params: &EvalParams,
+    nodes: &mut u64,
+) -> Option<Move> {
+    let mut best: Option<Move> = None;
+    let mut alpha = -i32::MAX;
+
+    for m in board.legal_moves() {
+        let undo = board.make_move(m.clone());
+        let score = -alpha_beta_counted(
+            board,
+            depth.saturating_sub(1),
+            -i32::MAX,
+            -alpha,
+            params,
+            nodes,
+        );
+        board.unmake_move(undo);
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some(m);
+        }
+    }
+    best
+}
+
+/// Searches every legal move for the side to move and summarises each one, sorted from the
+/// best scoring move to the worst.
+///
+/// A depth of 1 scores each move by the position it immediately leads to.
+pub fn analyze(board: &Board, depth: u8) -> Vec<MoveAnalysis> {
+    analyze_with(board, depth, &EvalParams::default())
+}
+
+/// Analyzes every legal move like `analyze`, scoring positions with `params`.
+pub fn analyze_with(board: &Board, depth: u8, params: &EvalParams) -> Vec<MoveAnalysis> {
+    let mut scratch = board.clone();
+    let mut analysis: Vec<MoveAnalysis> = board
+        .legal_moves()
+        .into_iter()
+        .map(|m| {
+            let san = m.to_san(board);
+            let undo = scratch.make_move(m.clone());
+            let gives_check = scratch.is_in_check(scratch.side_to_move());
+            let score = -minimax_counted(&mut scratch, depth.saturating_sub(1), params, &mut 0);
+            scratch.unmake_move(undo);
+            MoveAnalysis {
+                gives_check,
+                score,
+                capturing: m.capturing,
+                chess_move: m,
+                san,
+            }
+        })
+        .collect();
+
+    analysis.sort_by_key(|a| Reverse(a.score));
+    analysis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::piece_info::{PieceLoc, PieceType};
+    use crate::game::piece::Piece;
+
+    #[test]
+    fn test_analyze_returns_every_legal_move_sorted_best_first() {
+        let board = Board::new();
+        let analysis = analyze(&board, 2);
+
+        assert_eq!(board.legal_moves().len(), analysis.len());
+        assert!(analysis
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn test_analyze_ranks_free_capture_first() {
+        // The black queen hangs on d3, where both the c- and e-pawns attack it
+        let board =
+            Board::from_fen("rnb1kbnr/pppppppp/8/8/8/3q4/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        let analysis = analyze(&board, 1);
+        let best = &analysis[0];
+
+        assert!(best.capturing);
+        assert_eq!(PieceLoc::new(2, 3), best.chess_move.end_pos);
+        // A queen up, give or take where the pieces stand
+        assert!((800..1000).contains(&best.score));
+    }
+
+    // Picks the move `analyze` ranks first using plain minimax, counting the nodes searched
+    fn minimax_best_move(board: &Board, depth: u8) -> (Option<Move>, u64) {
+        let mut nodes = 0;
+        let mut scratch = board.clone();
+        let best = board
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let undo = scratch.make_move(m.clone());
+                let score =
+                    -minimax_counted(&mut scratch, depth - 1, &EvalParams::default(), &mut nodes);
+                scratch.unmake_move(undo);
+                (m, score)
+            })
+            .fold(None, |best: Option<(Move, i32)>, (m, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((m, score)),
+            });
+        (best.map(|(m, _)| m), nodes)
+    }
+
+    #[test]
+    fn test_quiescence_sees_recapture() {
+        // 1. e4 d5 2. Nc3 dxe4 leaves white a pawn down, but Nxe4 wins it straight back
+        let mut board = Board::new();
+        for san in ["e4", "d5", "Nc3", "dxe4"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        let m = Move::from_san(&board, "Nxe4").unwrap();
+        let recaptured = board.clone().move_piece(m).unwrap();
+
+        assert!(evaluate(&board) < -50);
+        assert_eq!(
+            -evaluate(&recaptured),
+            quiescence(&board, -i32::MAX, i32::MAX)
+        );
+    }
+
+    #[test]
+    fn test_best_move_avoids_losing_queen_to_recapture() {
+        // The d5 pawn is defended from e6, so Qxd5 only looks good without quiescence
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(0, 3),
+                Piece::new(PieceType::Queen, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(4, 3),
+                Piece::new(PieceType::Pawn, PieceColor::Black),
+            )
+            .with_piece(
+                PieceLoc::new(5, 4),
+                Piece::new(PieceType::Pawn, PieceColor::Black),
+            )
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::King, PieceColor::Black),
+            );
+
+        let m = best_move(&board, 1).unwrap();
+        assert_ne!("Qxd5", m.to_san(&board));
+    }
+
+    #[test]
+    fn test_best_move_with_takes_the_piece_params_value_most() {
+        // The queen can take either an undefended knight or an undefended bishop
+        let board = Board::from_fen("4k3/8/8/3n4/b7/8/8/3QK3 w - - 0 1").unwrap();
+        let prize_knight = EvalParams {
+            knight: 1000,
+            ..EvalParams::default()
+        };
+        let prize_bishop = EvalParams {
+            bishop: 1000,
+            ..EvalParams::default()
+        };
+
+        let m = best_move_with(&board, 1, &prize_knight).unwrap();
+        assert_eq!("Qxd5", m.to_san(&board));
+        let m = best_move_with(&board, 1, &prize_bishop).unwrap();
+        assert_eq!("Qxa4+", m.to_san(&board));
+    }
+
+    #[test]
+    fn test_searches_with_params_score_by_those_params() {
+        let board = Board::from_fen("4k3/8/8/3n4/b7/8/8/3QK3 w - - 0 1").unwrap();
+        let prize_knight = EvalParams {
+            knight: 1000,
+            ..EvalParams::default()
+        };
+
+        assert_eq!("Qxd5", analyze_with(&board, 1, &prize_knight)[0].san);
+        assert_eq!(
+            minimax_with(&board, 2, &prize_knight),
+            alpha_beta_with(&board, 2, -i32::MAX, i32::MAX, &prize_knight)
+        );
+
+        // Nothing can take the knight, so it weighs on every score from here on
+        let board = Board::from_fen("4k3/8/8/3n4/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(minimax_with(&board, 1, &prize_knight) < minimax(&board, 1) - 500);
+        assert!(
+            quiescence_with(&board, -i32::MAX, i32::MAX, &prize_knight)
+                < quiescence(&board, -i32::MAX, i32::MAX) - 500
+        );
+    }
+
+    #[test]
+    fn test_alpha_beta_matches_minimax() {
+        let hanging_queen =
+            Board::from_fen("rnb1kbnr/pppppppp/8/8/8/3q4/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        let mut fools_mate = Board::new();
+        for san in ["f3", "e5", "g4"] {
+            let m = Move::from_san(&fools_mate, san).unwrap();
+            fools_mate = fools_mate.move_piece(m).unwrap();
+        }
+
+        for mut board in [Board::new(), hanging_queen, fools_mate] {
+            let (expected, minimax_nodes) = minimax_best_move(&board, 3);
+            let (fen, hash) = (board.to_fen(), board.position_hash());
+            let mut alpha_beta_nodes = 0;
+            let actual =
+                best_move_counted(&mut board, 3, &EvalParams::default(), &mut alpha_beta_nodes);
+
+            assert_eq!(expected, actual);
+            // Every move the search made was taken back again
+            assert_eq!(fen, board.to_fen());
+            assert_eq!(hash, board.position_hash());
+            assert!(alpha_beta_nodes < minimax_nodes);
+            assert_eq!(
+                minimax(&board, 3),
+                alpha_beta(&board, 3, -i32::MAX, i32::MAX)
+            );
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::io;
+
+use chess_rust::book::Book;
+use chess_rust::uci;
+
+// Runs the engine as a UCI engine for chess GUIs, talking over stdin and stdout.
+// `--book <path>` plays from a Polyglot opening book until the game leaves it.
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let book = match args.iter().position(|arg| arg == "--book") {
+        Some(i) => {
+            let path = args.get(i + 1).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "--book needs a path")
+            })?;
+            Some(Book::load(path)?)
+        }
+        None => None,
+    };
+
+    uci::run_with_book(io::stdin().lock(), &mut io::stdout(), book.as_ref())
+}
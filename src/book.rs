@@ -0,0 +1,294 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::game::board::Board;
+use crate::game::moves::{move_checker, Move};
+use crate::game::piece::piece_info::{PieceColor, PieceLoc, PieceType};
+
+mod keys;
+
+use self::keys::RANDOM64;
+
+// Offsets of each group of keys within RANDOM64
+const CASTLING_KEYS: usize = 768;
+const EN_PASSANT_KEYS: usize = 772;
+const TURN_KEY: usize = 780;
+
+// Each book entry is a big-endian key, move, weight and learning value
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BookEntry {
+    key: u64,
+    raw_move: u16,
+    weight: u16,
+}
+
+/// A Polyglot opening book, mapping positions to the moves played from them and how
+/// often each should be chosen.
+#[derive(Clone, Debug, Default)]
+pub struct Book {
+    // Sorted by key, so every move for a position sits together
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    /// Reads a Polyglot `.bin` book from disk.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Book> {
+        Book::from_bytes(&fs::read(path)?)
+    }
+
+    /// Reads a Polyglot book that is already in memory.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Book> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "A Polyglot book must be made of whole 16 byte entries",
+            ));
+        }
+
+        let mut entries: Vec<BookEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| BookEntry {
+                key: u64::from_be_bytes(entry[0..8].try_into().unwrap()),
+                raw_move: u16::from_be_bytes(entry[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(entry[10..12].try_into().unwrap()),
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.key);
+        Ok(Book { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Picks a book move for the position, favouring moves in proportion to their weight,
+    /// or None if the position is out of book.
+    pub fn probe(&self, board: &Board) -> Option<Move> {
+        self.probe_with(board, &mut rand::thread_rng())
+    }
+
+    /// Like `probe`, but draws from the given random number generator.
+    pub fn probe_with(&self, board: &Board, rng: &mut impl Rng) -> Option<Move> {
+        let key = polyglot_key(board);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+
+        // Entries whose move isn't legal here can only come from a hash collision
+        let candidates: Vec<(Move, u16)> = self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| Some((decode_move(board, entry.raw_move)?, entry.weight)))
+            .collect();
+
+        candidates
+            .choose_weighted(rng, |(_, weight)| *weight)
+            .ok()
+            .map(|(m, _)| m.clone())
+    }
+}
+
+/// Hashes a standard 8x8 position the way Polyglot books index it.
+pub fn polyglot_key(board: &Board) -> u64 {
+    let mut key = 0;
+
+    for color in [PieceColor::White, PieceColor::Black] {
+        for (loc, piece) in board.pieces_of_color(color) {
+            let kind = 2 * piece_index(piece.piece_type) + usize::from(color == PieceColor::White);
+            key ^= RANDOM64[64 * kind + usize::from(loc.rank) * 8 + usize::from(loc.file)];
+        }
+    }
+
+    for (offset, color, kingside) in [
+        (0, PieceColor::White, true),
+        (1, PieceColor::White, false),
+        (2, PieceColor::Black, true),
+        (3, PieceColor::Black, false),
+    ] {
+        if board.castling_rook_file(color, kingside).is_some() {
+            key ^= RANDOM64[CASTLING_KEYS + offset];
+        }
+    }
+
+    // The en passant file only counts if a pawn is actually beside the one that just
+    // moved, ready to capture it
+    if let Some(target) = board.en_passant_target {
+        // The capturing pawn stands on the same rank as the pawn it would take
+        let pawn_rank = move_checker::en_passant_victim(target, board.side_to_move()).rank;
+        let can_capture = [target.file.checked_sub(1), Some(target.file + 1)]
+            .into_iter()
+            .flatten()
+            .filter(|&file| file < board.files)
+            .any(|file| {
+                board
+                    .get_piece_at_location(PieceLoc::new(pawn_rank, file))
+                    .is_some_and(|piece| {
+                        piece.piece_type == PieceType::Pawn && piece.color == board.side_to_move()
+                    })
+            });
+        if can_capture {
+            key ^= RANDOM64[EN_PASSANT_KEYS + usize::from(target.file)];
+        }
+    }
+
+    if board.side_to_move() == PieceColor::White {
+        key ^= RANDOM64[TURN_KEY];
+    }
+    key
+}
+
+fn piece_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+// Polyglot packs a move into the to square, from square and promotion piece, three bits
+// each for file and rank. Castling is written as the king capturing its own rook.
+fn decode_move(board: &Board, raw_move: u16) -> Option<Move> {
+    let field = |shift: u16| ((raw_move >> shift) & 0b111) as u8;
+    let start = PieceLoc::new(field(9), field(6));
+    let mut end = PieceLoc::new(field(3), field(0));
+    let promotion = match field(12) {
+        0 => None,
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => return None,
+    };
+
+    let piece = board.get_piece_at_location(start)?;
+    let onto_own_rook = board
+        .get_piece_at_location(end)
+        .is_some_and(|target| target.piece_type == PieceType::Rook && target.color == piece.color);
+    if piece.piece_type == PieceType::King && onto_own_rook {
+        end.file = if end.file > start.file {
+            start.file + 2
+        } else {
+            start.file - 2
+        };
+    }
+
+    board
+        .legal_moves()
+        .into_iter()
+        .find(|m| m.start_pos == start && m.end_pos == end && m.promotion == promotion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn encode_move(start: &str, end: &str) -> u16 {
+        let start = PieceLoc::from_notation(start).unwrap();
+        let end = PieceLoc::from_notation(end).unwrap();
+        (u16::from(start.rank) << 9)
+            | (u16::from(start.file) << 6)
+            | (u16::from(end.rank) << 3)
+            | u16::from(end.file)
+    }
+
+    fn book_bytes(entries: &[(u64, u16, u16)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (key, raw_move, weight) in entries {
+            bytes.extend_from_slice(&key.to_be_bytes());
+            bytes.extend_from_slice(&raw_move.to_be_bytes());
+            bytes.extend_from_slice(&weight.to_be_bytes());
+            bytes.extend_from_slice(&0u32.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_polyglot_key_reference_positions() {
+        // From the Polyglot format specification
+        for (fen, key) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                0x463b96181691fc9c,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c9b50fd114196,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a48b5a8e47ff78,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fdd303c946bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f9b829b279560,
+            ),
+        ] {
+            assert_eq!(key, polyglot_key(&Board::from_fen(fen).unwrap()), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_probe_picks_weighted_book_move() {
+        let start_key = polyglot_key(&Board::new());
+        let bytes = book_bytes(&[
+            (start_key, encode_move("e2", "e4"), 3),
+            (start_key, encode_move("d2", "d4"), 1),
+            // Never chosen, as it has no weight
+            (start_key, encode_move("g1", "f3"), 0),
+            (start_key.wrapping_add(1), encode_move("a2", "a4"), 10),
+        ]);
+        let book = Book::from_bytes(&bytes).unwrap();
+        let board = Board::new();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let mut e4_count = 0;
+        for _ in 0..100 {
+            let san = book.probe_with(&board, &mut rng).unwrap().to_san(&board);
+            assert!(san == "e4" || san == "d4");
+            if san == "e4" {
+                e4_count += 1;
+            }
+        }
+        assert!(e4_count > 50);
+
+        let out_of_book = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(None, book.probe(&out_of_book));
+    }
+
+    #[test]
+    fn test_probe_decodes_castling() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let bytes = book_bytes(&[(polyglot_key(&board), encode_move("e1", "h1"), 1)]);
+        let book = Book::from_bytes(&bytes).unwrap();
+
+        assert_eq!("O-O", book.probe(&board).unwrap().to_san(&board));
+    }
+
+    #[test]
+    fn test_load_from_disk() {
+        let path = std::env::temp_dir().join("chess_rust_test_book.bin");
+        fs::write(&path, book_bytes(&[(1, 0, 1), (2, 0, 1)])).unwrap();
+        let book = Book::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(2, book.len());
+        assert!(Book::from_bytes(&[0; 15]).is_err());
+    }
+}
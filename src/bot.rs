@@ -0,0 +1,84 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::ai;
+use crate::game::board::Board;
+use crate::game::moves::{generate_legal_moves, Move};
+use crate::game::status::GameStatus;
+
+/// Picks one of the side to move's legal moves uniformly at random, or None once the game
+/// is over because there are none left.
+pub fn random_move(board: &Board, rng: &mut impl Rng) -> Option<Move> {
+    generate_legal_moves(board).choose(rng).cloned()
+}
+
+/// Plays the engine against itself from the starting position, searching `depth` plies
+/// for both sides, until the game ends or `max_moves` halfmoves have been played.
+///
+/// Returns the result, which isn't over yet if the cap was reached, along with every
+/// move played. Draws that would have to be claimed are played through.
+pub fn play_self(depth: u8, max_moves: usize) -> (GameStatus, Vec<Move>) {
+    let mut board = Board::new();
+    while board.move_list.len() < max_moves && !board.game_status().is_over() {
+        let Some(m) = ai::best_move(&board, depth) else {
+            break;
+        };
+        // The search only hands back legal moves, so there's no need to check it again
+        board.make_move(m);
+    }
+    (board.game_status(), board.move_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random_move_is_legal() {
+        let board = Board::new();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let m = random_move(&board, &mut rng).unwrap();
+        assert!(board.legal_moves().contains(&m));
+    }
+
+    #[test]
+    fn test_random_move_none_after_checkmate() {
+        let mut board = Board::new();
+        for san in ["f3", "e5", "g4", "Qh4"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(None, random_move(&board, &mut rng));
+    }
+
+    #[test]
+    fn test_play_self_stops_at_move_cap() {
+        let (status, moves) = play_self(1, 12);
+
+        assert_eq!(GameStatus::Ongoing, status);
+        assert_eq!(12, moves.len());
+        assert!(Board::replay(&moves).is_ok());
+    }
+
+    #[test]
+    fn test_random_games_play_without_panicking() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..3 {
+            let mut board = Board::new();
+            for _ in 0..40 {
+                let Some(m) = random_move(&board, &mut rng) else {
+                    break;
+                };
+                board = board.move_piece(m).unwrap();
+                if board.game_status().is_over() {
+                    break;
+                }
+            }
+        }
+    }
+}
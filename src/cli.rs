@@ -1,6 +1,8 @@
 use crate::game::board::Board;
-use crate::game::moves::Move;
-use crate::game::piece::piece_info::PieceLoc;
+use crate::game::moves::{move_checker::MoveError, uci::UciMoveError, Move, PROMOTION_PIECE_TYPES};
+use crate::game::piece::piece_info::{PieceLoc, PieceType};
+use crate::game::piece::Piece;
+use crate::game::Game;
 use std::io::{self, Write};
 
 fn get_input(prompt: &str) -> io::Result<String> {
@@ -13,34 +15,236 @@ fn get_input(prompt: &str) -> io::Result<String> {
     Ok(buffer)
 }
 
-fn prompt_location() -> Option<PieceLoc> {
-    if let Ok(position) = get_input("Enter piece position (i.e. A1, E5): ") {
-        let position = position.trim();
-        let position = PieceLoc::from_notation(position);
-        return position;
+/// What a player can do on their turn besides moving a piece.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Move(Move),
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    ClaimDraw,
+    Undo,
+}
+
+fn prompt_location(game: &Board) -> Option<PieceLoc> {
+    match get_input("Enter piece position (i.e. A1, E5): ") {
+        Ok(position) => parse_location(game, &position),
+        Err(_) => None,
     }
-    println!("Please enter a valid rank and file, from A-H, 1-8.");
+}
+
+fn parse_location(game: &Board, position: &str) -> Option<PieceLoc> {
+    match game.parse_square(position.trim()) {
+        Ok(location) => return Some(location),
+        Err(e) => println!("{}", e),
+    }
+    let last_square = PieceLoc::new(game.ranks - 1, game.files - 1);
+    println!(
+        "Please enter a square on the board, from a1 to {}.",
+        last_square
+    );
     None
 }
 
+fn prompt_promotion() -> Option<PieceType> {
+    if let Ok(choice) = get_input("Promote pawn to (Q/R/B/N): ") {
+        let mut chars = choice.trim().chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            let promotion = PieceType::from_char(c.to_ascii_uppercase())
+                .filter(|piece_type| PROMOTION_PIECE_TYPES.contains(piece_type));
+            if promotion.is_some() {
+                return promotion;
+            }
+        }
+    }
+    println!("Please enter one of Q, R, B, or N.");
+    None
+}
+
+// Warns the side to move about any of their pieces the opponent could win
+fn print_hanging_warnings(game: &Board) {
+    for loc in game.hanging_pieces(game.side_to_move()) {
+        if let Some(piece) = game.get_piece_at_location(loc) {
+            println!(
+                "Your {} on {} is hanging!",
+                piece.piece_type.to_string().to_lowercase(),
+                loc
+            );
+        }
+    }
+}
+
+// Shows the squares the selected piece can legally move to, both on the board and listed
+fn print_move_hints(game: &Board, location: PieceLoc) {
+    let mut destinations: Vec<PieceLoc> = game
+        .legal_moves_for(location)
+        .iter()
+        .map(|m| m.end_pos)
+        .collect();
+    // Each promotion is its own move, but they all land on the same square
+    destinations.dedup();
+
+    if destinations.is_empty() {
+        println!("That piece has no legal moves.");
+    } else {
+        println!(
+            "{}",
+            game.to_string_highlighted(game.side_to_move(), &destinations)
+        );
+        let names: Vec<String> = destinations.iter().map(|loc| loc.to_string()).collect();
+        println!("It can move to: {}", names.join(", "));
+    }
+}
+
+/// Asks the player for a move, either all at once like "e2e4" or "e7e8q", or by picking a
+/// piece and then where it goes.
 pub fn prompt_make_move(game: &Board) -> Option<Move> {
-    if let Some(location) = prompt_location() {
-        if let Some(piece) = game.get_piece_at_location(location) {
-            println!("Piece found: {:?}", piece);
-
-            if let Some(target_location) = prompt_location() {
-                let new_move = Move::new(game, &piece, &location, &target_location);
-                match new_move {
-                    Ok(m) => return Some(m),
-                    Err(e) => {
-                        println!("{}", e);
-                        return None;
-                    }
+    let input = get_input("Enter piece position (i.e. A1, E5) or a move (i.e. e2e4): ").ok()?;
+    parse_move_input(game, &input.trim().to_ascii_lowercase())
+}
+
+/// Asks the player to pick a piece to move, or type a whole move like "e2e4", or to type
+/// "resign", "offer draw", "accept", "claim" or "undo" in its place.
+pub fn prompt_command(game: &Board) -> Option<Command> {
+    print_hanging_warnings(game);
+    let input = get_input(
+        "Enter piece position (i.e. A1, E5) or a move (i.e. e2e4), or resign, offer draw, accept, claim, undo: ",
+    )
+    .ok()?;
+    match input.trim().to_ascii_lowercase().as_str() {
+        "resign" => Some(Command::Resign),
+        "offer draw" | "draw" => Some(Command::OfferDraw),
+        "accept" | "accept draw" => Some(Command::AcceptDraw),
+        "claim" | "claim draw" => Some(Command::ClaimDraw),
+        "undo" => Some(Command::Undo),
+        position => parse_move_input(game, position).map(Command::Move),
+    }
+}
+
+// Reads a whole move written as in UCI, falling back to asking where the piece on the
+// given square should go when the input isn't one
+fn parse_move_input(game: &Board, input: &str) -> Option<Move> {
+    match Move::from_uci(game, input) {
+        Ok(m) => return Some(m),
+        // Written as a move, but not one that can be played here
+        Err(e @ UciMoveError::NoMatchingMove) => {
+            println!("{}", e);
+            return None;
+        }
+        Err(_) => (),
+    }
+    parse_location(game, input).and_then(|location| prompt_move_from(game, location))
+}
+
+// Explains why the piece can't be picked when it belongs to the side not on move
+fn wrong_side_message(game: &Board, piece: &Piece) -> Option<String> {
+    if piece.color == game.side_to_move() {
+        return None;
+    }
+    Some(format!("It's {}'s turn.", game.side_to_move()))
+}
+
+// Asks where the piece on `location` should go, and what it promotes to if it must
+fn prompt_move_from(game: &Board, location: PieceLoc) -> Option<Move> {
+    if let Some(piece) = game.get_piece_at_location(location) {
+        if let Some(message) = wrong_side_message(game, &piece) {
+            println!("{}", message);
+            return None;
+        }
+        println!("Piece found: {:?}", piece);
+        print_move_hints(game, location);
+
+        if let Some(target_location) = prompt_location(game) {
+            let mut new_move = Move::new(game, &piece, &location, &target_location);
+            if let Err(MoveError::PromotionRequired) = new_move {
+                if let Some(promotion) = prompt_promotion() {
+                    new_move = Move::new_with_promotion(
+                        game,
+                        &piece,
+                        &location,
+                        &target_location,
+                        Some(promotion),
+                    );
+                }
+            }
+            match new_move {
+                Ok(m) => return Some(m),
+                Err(e) => {
+                    println!("{}", e);
+                    return None;
                 }
             }
-        } else {
-            println!("No piece found at ({location:?})");
         }
+    } else {
+        println!("No piece found at ({location:?})");
     }
     None
 }
+
+/// Plays the moves in a script, one per line in SAN or UCI, i.e. "Nf3" or "g1f3", so a
+/// game can be picked up from a known position. Blank lines are skipped.
+///
+/// Stops at the first line that can't be played, reporting its line number and why. The
+/// moves before it stay played.
+pub fn play_moves_script(game: &mut Game, script: &str) -> Result<(), String> {
+    for (i, line) in script.lines().enumerate() {
+        let notation = line.trim();
+        if notation.is_empty() {
+            continue;
+        }
+        let report = |e: &dyn std::fmt::Display| format!("Line {}: {}", i + 1, e);
+
+        let m = match Move::from_uci(game.board(), notation) {
+            Ok(m) => m,
+            Err(_) => Move::from_san(game.board(), notation).map_err(|e| report(&e))?,
+        };
+        game.try_move(m).map_err(|e| report(&e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::piece_info::PieceColor;
+
+    #[test]
+    fn test_wrong_side_message() {
+        let board = Board::new();
+        let white_pawn = Piece::new(PieceType::Pawn, PieceColor::White);
+        let black_pawn = Piece::new(PieceType::Pawn, PieceColor::Black);
+
+        assert_eq!(None, wrong_side_message(&board, &white_pawn));
+        assert_eq!(
+            Some(String::from("It's White's turn.")),
+            wrong_side_message(&board, &black_pawn)
+        );
+
+        let board = board.with_turn(PieceColor::Black);
+        assert_eq!(
+            Some(String::from("It's Black's turn.")),
+            wrong_side_message(&board, &white_pawn)
+        );
+    }
+
+    #[test]
+    fn test_play_moves_script_mixes_san_and_uci() {
+        let mut game = Game::new();
+        play_moves_script(&mut game, "e4\ne7e5\n\n  Nf3  \nb8c6\n").unwrap();
+
+        assert_eq!(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            game.board().to_fen()
+        );
+    }
+
+    #[test]
+    fn test_play_moves_script_reports_bad_line() {
+        let mut game = Game::new();
+        let error = play_moves_script(&mut game, "e4\ne5\nKe3\nNf3\n").unwrap_err();
+
+        assert!(error.starts_with("Line 3: "), "{}", error);
+        // The moves before the bad line were still played
+        assert_eq!(2, game.board().move_list.len());
+    }
+}
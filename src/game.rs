@@ -1,3 +1,491 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use self::board::{Board, BoardSnapshot};
+use self::clock::Clock;
+use self::moves::{move_checker::MoveError, Move};
+use self::piece::piece_info::PieceColor;
+use self::status::{DrawReason, GameStatus};
+
+pub mod bitboard;
 pub mod board;
+pub mod clock;
 pub mod moves;
 pub mod piece;
+pub mod status;
+
+/// A game being played: the board, how the game stands, and who is playing it.
+///
+/// `Board` knows the rules of chess, while `Game` keeps track of one game played under
+/// them, refusing any moves once it is over.
+#[derive(Clone, Debug)]
+pub struct Game {
+    board: Board,
+    status: GameStatus,
+    // The side that offered a draw, until the other side accepts it or moves instead
+    draw_offer: Option<PieceColor>,
+    // Empty unless the game is played with a time control
+    clocks: HashMap<PieceColor, Clock>,
+    // When the side to move's clock started running, which is None until the first press
+    turn_started: Option<Instant>,
+    // The board as it stood before each move played in this game, for taking moves back
+    history: Vec<BoardSnapshot>,
+    pub white_name: Option<String>,
+    pub black_name: Option<String>,
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::from_board(Board::new())
+    }
+
+    /// Picks up a game from any position, i.e. one loaded with `Board::from_fen`.
+    pub fn from_board(board: Board) -> Game {
+        Game {
+            status: board.game_status(),
+            board,
+            draw_offer: None,
+            clocks: HashMap::new(),
+            turn_started: None,
+            history: Vec::new(),
+            white_name: None,
+            black_name: None,
+        }
+    }
+
+    pub fn with_player_names(mut self, white: &str, black: &str) -> Game {
+        self.white_name = Some(white.to_string());
+        self.black_name = Some(black.to_string());
+        self
+    }
+
+    /// Plays the game with a time control, where each player starts with `initial` on
+    /// their clock and gains `increment` back after every move.
+    pub fn with_time_control(mut self, initial: Duration, increment: Duration) -> Game {
+        let clock = Clock::new(initial, increment);
+        self.clocks = HashMap::from([(PieceColor::White, clock), (PieceColor::Black, clock)]);
+        self
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    pub fn current_turn(&self) -> PieceColor {
+        self.board.side_to_move()
+    }
+
+    /// Plays a move for the side to move, leaving the game untouched if it is illegal or
+    /// the game has already ended.
+    pub fn try_move(&mut self, m: Move) -> Result<(), MoveError> {
+        self.ensure_ongoing()?;
+
+        let next = self.board.clone().move_piece(m)?;
+        self.history.push(self.board.snapshot());
+        self.board = next;
+        self.status = self.board.game_status();
+        // Moving instead of accepting declines the opponent's offer
+        if self.draw_offer == Some(self.current_turn()) {
+            self.draw_offer = None;
+        }
+        Ok(())
+    }
+
+    /// Takes back the last move played in this game, returning it, or None if there is
+    /// nothing to take back. A game the move ended is picked up again, and any standing
+    /// draw offer is withdrawn. The clocks are left as they are.
+    pub fn undo_move(&mut self) -> Option<Move> {
+        let previous = self.history.pop()?;
+        let m = self.board.move_list.last().cloned();
+        self.board.restore(previous);
+        self.status = self.board.game_status();
+        self.draw_offer = None;
+        m
+    }
+
+    /// Ends the game with `color` conceding it to their opponent.
+    pub fn resign(&mut self, color: PieceColor) -> Result<(), MoveError> {
+        self.ensure_ongoing()?;
+        self.status = GameStatus::Resignation {
+            winner: color.flip(),
+        };
+        Ok(())
+    }
+
+    /// Offers a draw on behalf of the side to move. The offer stands until the opponent
+    /// accepts it with `accept_draw` or declines it by making a move.
+    pub fn offer_draw(&mut self) -> Result<(), MoveError> {
+        self.ensure_ongoing()?;
+        self.draw_offer = Some(self.current_turn());
+        Ok(())
+    }
+
+    /// The side that has a draw offer standing, if any.
+    pub fn draw_offer(&self) -> Option<PieceColor> {
+        self.draw_offer
+    }
+
+    /// Accepts the opponent's standing draw offer on behalf of the side to move.
+    pub fn accept_draw(&mut self) -> Result<(), MoveError> {
+        self.ensure_ongoing()?;
+        if self.draw_offer != Some(self.current_turn().flip()) {
+            return Err(MoveError::NoDrawOffer);
+        }
+        self.status = GameStatus::DrawAgreed;
+        self.draw_offer = None;
+        Ok(())
+    }
+
+    /// Claims the draw on offer in the current position, by threefold repetition or the
+    /// fifty-move rule, on behalf of the side to move. Until it is claimed the game goes on.
+    pub fn claim_draw(&mut self) -> Result<(), MoveError> {
+        self.ensure_ongoing()?;
+        match self.status {
+            GameStatus::Draw {
+                reason,
+                claimable: true,
+            } => {
+                self.status = GameStatus::DrawClaimed { reason };
+                self.draw_offer = None;
+                Ok(())
+            }
+            _ => Err(MoveError::NoDrawToClaim),
+        }
+    }
+
+    /// The time left on `color`'s clock as of their last press, or None if the game has no
+    /// time control.
+    pub fn time_remaining(&self, color: PieceColor) -> Option<Duration> {
+        self.clocks.get(&color).map(|clock| clock.remaining())
+    }
+
+    /// Stops the clock of the player who just moved and starts their opponent's.
+    ///
+    /// The clocks start running at the first press, so white's first move is untimed.
+    /// Every press after that charges the mover for the time since the previous one and
+    /// adds their increment.
+    pub fn press_clock(&mut self) {
+        self.press_clock_at(Instant::now());
+    }
+
+    /// Like `press_clock`, but taking the current time from the caller.
+    pub fn press_clock_at(&mut self, now: Instant) {
+        if self.clocks.is_empty() || self.status.is_over() {
+            return;
+        }
+
+        let mover = self.current_turn().flip();
+        if let (Some(started), Some(clock)) = (self.turn_started, self.clocks.get_mut(&mover)) {
+            clock.spend(now.saturating_duration_since(started));
+        }
+        self.turn_started = Some(now);
+        self.check_flag(mover);
+    }
+
+    /// Ends the game if the side to move has used up their time without moving yet.
+    pub fn check_time(&mut self) {
+        self.check_time_at(Instant::now());
+    }
+
+    /// Like `check_time`, but taking the current time from the caller.
+    pub fn check_time_at(&mut self, now: Instant) {
+        if self.status.is_over() {
+            return;
+        }
+
+        let color = self.current_turn();
+        if let (Some(started), Some(clock)) = (self.turn_started, self.clocks.get(&color)) {
+            if now.saturating_duration_since(started) >= clock.remaining() {
+                self.clocks
+                    .insert(color, Clock::new(Duration::ZERO, clock.increment()));
+                self.check_flag(color);
+            }
+        }
+    }
+
+    // A player out of time loses, unless their opponent could never mate them whatever
+    // either side played
+    fn check_flag(&mut self, color: PieceColor) {
+        if !self
+            .clocks
+            .get(&color)
+            .is_some_and(|clock| clock.is_flagged())
+        {
+            return;
+        }
+        self.status = if !self.board.could_checkmate(color.flip()) {
+            GameStatus::draw(DrawReason::InsufficientMaterial)
+        } else {
+            GameStatus::Timeout {
+                winner: color.flip(),
+            }
+        };
+    }
+
+    fn ensure_ongoing(&self) -> Result<(), MoveError> {
+        if self.status.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        Ok(())
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::piece_info::PieceLoc;
+
+    fn play(game: &mut Game, sans: &[&str]) -> Result<(), MoveError> {
+        for san in sans {
+            let m = Move::from_san(game.board(), san).expect("Move should parse");
+            game.try_move(m)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_move_advances_the_game() {
+        let mut game = Game::new().with_player_names("Alice", "Bob");
+        play(&mut game, &["e4"]).unwrap();
+
+        assert_eq!(PieceColor::Black, game.current_turn());
+        assert_eq!(GameStatus::Ongoing, game.status());
+        assert_eq!(Some(String::from("Alice")), game.white_name);
+    }
+
+    #[test]
+    fn test_try_move_rejects_illegal_move() {
+        let mut game = Game::new();
+        let m = Move {
+            end_pos: PieceLoc::new(3, 1),
+            ..Move::from_san(game.board(), "Nc3").unwrap()
+        };
+
+        assert!(game.try_move(m).is_err());
+        assert_eq!(PieceColor::White, game.current_turn());
+        assert!(game.board().move_list.is_empty());
+    }
+
+    #[test]
+    fn test_undo_move_takes_back_moves_in_order() {
+        let mut game = Game::new();
+        assert_eq!(None, game.undo_move());
+
+        play(&mut game, &["e4", "e5", "Nf3"]).unwrap();
+        let undone = game.undo_move().unwrap();
+        assert_eq!("Nf3", undone.to_san(game.board()));
+        assert_eq!(PieceColor::White, game.current_turn());
+
+        game.undo_move().unwrap();
+        game.undo_move().unwrap();
+        assert!(game.board().same_position(&Board::new()));
+        assert_eq!(None, game.undo_move());
+    }
+
+    #[test]
+    fn test_undo_move_reopens_a_finished_game() {
+        let mut game = Game::new();
+        play(&mut game, &["f3", "e5", "g4", "Qh4"]).unwrap();
+        assert_ne!(GameStatus::Ongoing, game.status());
+
+        game.undo_move().unwrap();
+        assert_eq!(GameStatus::Ongoing, game.status());
+        play(&mut game, &["Qf6"]).unwrap();
+    }
+
+    #[test]
+    fn test_try_move_refuses_moves_after_checkmate() {
+        let mut game = Game::new();
+        play(&mut game, &["f3", "e5", "g4"]).unwrap();
+        let late_move = Move::from_san(game.board(), "Qh4").unwrap();
+        play(&mut game, &["Qh4"]).unwrap();
+
+        assert_eq!(
+            GameStatus::Checkmate {
+                winner: PieceColor::Black
+            },
+            game.status()
+        );
+        assert_eq!(Err(MoveError::GameOver), game.try_move(late_move));
+    }
+
+    #[test]
+    fn test_resign_ends_the_game() {
+        let mut game = Game::new();
+        play(&mut game, &["e4"]).unwrap();
+        game.resign(PieceColor::Black).unwrap();
+
+        assert_eq!(
+            GameStatus::Resignation {
+                winner: PieceColor::White
+            },
+            game.status()
+        );
+        assert_eq!(Err(MoveError::GameOver), play(&mut game, &["e5"]));
+        assert_eq!(Err(MoveError::GameOver), game.resign(PieceColor::White));
+    }
+
+    #[test]
+    fn test_accepted_draw_offer_ends_the_game() {
+        let mut game = Game::new();
+        game.offer_draw().unwrap();
+        // The offer stands through the offering side's own move
+        play(&mut game, &["e4"]).unwrap();
+        assert_eq!(Some(PieceColor::White), game.draw_offer());
+
+        game.accept_draw().unwrap();
+        assert_eq!(GameStatus::DrawAgreed, game.status());
+    }
+
+    #[test]
+    fn test_moving_declines_draw_offer() {
+        let mut game = Game::new();
+        assert_eq!(Err(MoveError::NoDrawOffer), game.accept_draw());
+
+        play(&mut game, &["e4"]).unwrap();
+        game.offer_draw().unwrap();
+        // The side making the offer can't accept it themselves
+        assert_eq!(Err(MoveError::NoDrawOffer), game.accept_draw());
+
+        play(&mut game, &["e5", "Nf3"]).unwrap();
+        assert_eq!(None, game.draw_offer());
+        assert_eq!(Err(MoveError::NoDrawOffer), game.accept_draw());
+        assert_eq!(GameStatus::Ongoing, game.status());
+    }
+
+    #[test]
+    fn test_claimable_draw_goes_on_until_claimed() {
+        let mut game = Game::new();
+        assert_eq!(Err(MoveError::NoDrawToClaim), game.claim_draw());
+
+        let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+        play(&mut game, &shuffle).unwrap();
+        play(&mut game, &shuffle).unwrap();
+        assert_eq!(
+            GameStatus::draw(DrawReason::ThreefoldRepetition),
+            game.status()
+        );
+
+        // Nobody has claimed it, so play carries on
+        play(&mut game, &["e4"]).unwrap();
+        assert_eq!(GameStatus::Ongoing, game.status());
+        game.undo_move().unwrap();
+
+        game.claim_draw().unwrap();
+        assert_eq!(
+            GameStatus::DrawClaimed {
+                reason: DrawReason::ThreefoldRepetition
+            },
+            game.status()
+        );
+        assert_eq!(Err(MoveError::GameOver), play(&mut game, &["e4"]));
+    }
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn test_press_clock_charges_the_mover() {
+        let start = Instant::now();
+        let mut game = Game::new().with_time_control(secs(60), secs(1));
+
+        // White's first move starts the clocks without costing anything
+        play(&mut game, &["e4"]).unwrap();
+        game.press_clock_at(start);
+        play(&mut game, &["e5"]).unwrap();
+        game.press_clock_at(start + secs(10));
+        play(&mut game, &["Nf3"]).unwrap();
+        game.press_clock_at(start + secs(15));
+
+        assert_eq!(
+            Some(secs(60 - 5 + 1)),
+            game.time_remaining(PieceColor::White)
+        );
+        assert_eq!(
+            Some(secs(60 - 10 + 1)),
+            game.time_remaining(PieceColor::Black)
+        );
+        assert_eq!(None, Game::new().time_remaining(PieceColor::White));
+    }
+
+    #[test]
+    fn test_running_out_of_time_loses() {
+        let start = Instant::now();
+        let mut game = Game::new().with_time_control(secs(30), Duration::ZERO);
+        play(&mut game, &["e4"]).unwrap();
+        game.press_clock_at(start);
+
+        // Still thinking, but out of time
+        game.check_time_at(start + secs(29));
+        assert_eq!(GameStatus::Ongoing, game.status());
+        game.check_time_at(start + secs(30));
+        assert_eq!(
+            GameStatus::Timeout {
+                winner: PieceColor::White
+            },
+            game.status()
+        );
+        assert_eq!(Some(Duration::ZERO), game.time_remaining(PieceColor::Black));
+    }
+
+    #[test]
+    fn test_moving_too_late_still_loses_on_time() {
+        let start = Instant::now();
+        let mut game = Game::new().with_time_control(secs(30), secs(5));
+        play(&mut game, &["e4"]).unwrap();
+        game.press_clock_at(start);
+        play(&mut game, &["e5"]).unwrap();
+        game.press_clock_at(start + secs(31));
+
+        assert_eq!(
+            GameStatus::Timeout {
+                winner: PieceColor::White
+            },
+            game.status()
+        );
+    }
+
+    // Lets white run out of time after 1. e4 Ke7, with black's clock still running
+    fn flag_white(fen: &str) -> GameStatus {
+        let start = Instant::now();
+        let board = Board::from_fen(fen).unwrap();
+        let mut game = Game::from_board(board).with_time_control(secs(10), Duration::ZERO);
+        play(&mut game, &["e4"]).unwrap();
+        game.press_clock_at(start);
+        play(&mut game, &["Ke7"]).unwrap();
+        game.press_clock_at(start + secs(1));
+        game.check_time_at(start + secs(11));
+        game.status()
+    }
+
+    #[test]
+    fn test_timeout_against_a_lone_king_is_a_draw() {
+        // Black has nothing but the king, so could never mate
+        assert_eq!(
+            GameStatus::draw(DrawReason::InsufficientMaterial),
+            flag_white("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_timeout_loses_if_a_lone_knight_could_still_mate() {
+        // The knight can't force mate, but white's pawn could block its own king in
+        assert_eq!(
+            GameStatus::Timeout {
+                winner: PieceColor::Black
+            },
+            flag_white("4k2n/8/8/8/8/8/4P3/4K3 w - - 0 1")
+        );
+    }
+}
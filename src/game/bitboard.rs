@@ -0,0 +1,602 @@
+use core::fmt;
+
+use crate::game::board::{board_display, Board};
+use crate::game::moves::PROMOTION_PIECE_TYPES;
+use crate::game::piece::{
+    piece_info::{PieceColor, PieceLoc, PieceType},
+    Piece,
+};
+
+// Squares are numbered like the board's vector, a1 = 0 through h8 = 63, so bit `rank * 8 +
+// file` of a set is that square
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+const RANK_1: u64 = 0xff;
+const RANK_8: u64 = RANK_1 << 56;
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+// Castling rights, one bit per side per color
+const WHITE_KINGSIDE: u8 = 1;
+const WHITE_QUEENSIDE: u8 = 2;
+const BLACK_KINGSIDE: u8 = 4;
+const BLACK_QUEENSIDE: u8 = 8;
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+const KNIGHT_ATTACKS: [u64; 64] = leaper_attacks(&[
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+]);
+const KING_ATTACKS: [u64; 64] = leaper_attacks(&[
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+]);
+
+// Precomputes, for every square, the squares a piece jumping by the given offsets reaches
+const fn leaper_attacks(offsets: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let rank = (square / 8) as i8;
+        let file = (square % 8) as i8;
+        let mut i = 0;
+        while i < offsets.len() {
+            let (dr, df) = offsets[i];
+            let (r, f) = (rank + dr, file + df);
+            if r >= 0 && r < 8 && f >= 0 && f < 8 {
+                table[square] |= 1 << (r * 8 + f);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+// Walks each ray out from the square until it leaves the board or hits a piece, which is
+// included so captures show up as attacks
+fn slider_attacks(square: u8, occupied: u64, directions: &[(i8, i8)]) -> u64 {
+    let mut attacks = 0;
+    for &(dr, df) in directions {
+        let (mut r, mut f) = ((square / 8) as i8 + dr, (square % 8) as i8 + df);
+        while (0..8).contains(&r) && (0..8).contains(&f) {
+            let bit = 1 << (r * 8 + f);
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+            r += dr;
+            f += df;
+        }
+    }
+    attacks
+}
+
+// The squares pawns of the given color attack diagonally
+fn pawn_attacks(pawns: u64, color: PieceColor) -> u64 {
+    match color {
+        PieceColor::White => ((pawns & !FILE_A) << 7) | ((pawns & !FILE_H) << 9),
+        PieceColor::Black => ((pawns & !FILE_H) >> 7) | ((pawns & !FILE_A) >> 9),
+    }
+}
+
+fn color_index(color: PieceColor) -> usize {
+    match color {
+        PieceColor::White => 0,
+        PieceColor::Black => 1,
+    }
+}
+
+fn piece_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+fn square_of(loc: PieceLoc) -> u8 {
+    loc.rank * 8 + loc.file
+}
+
+// Yields the index of each set bit, lowest first
+fn squares_of(mut set: u64) -> impl Iterator<Item = u8> {
+    std::iter::from_fn(move || {
+        if set == 0 {
+            return None;
+        }
+        let square = set.trailing_zeros() as u8;
+        set &= set - 1;
+        Some(square)
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BitBoardError {
+    UnsupportedSize,
+    UnsupportedCastling,
+}
+
+impl fmt::Display for BitBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match *self {
+            BitBoardError::UnsupportedSize => "Only 8x8 boards can be converted.",
+            BitBoardError::UnsupportedCastling => {
+                "Castling is only supported with rooks starting in the corners."
+            }
+        };
+        write!(f, "Invalid BitBoard: {}", output)
+    }
+}
+
+// A move between two squares, which is all the bitboard needs to replay it
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BitMove {
+    from: u8,
+    to: u8,
+    promotion: Option<PieceType>,
+}
+
+/// A compact copy of a standard 8x8 position, holding one `u64` per piece type per color,
+/// for fast move generation and attack detection.
+///
+/// Only what is needed to generate moves is kept, so the move list, graveyard and clocks
+/// of the `Board` it came from are not carried over.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BitBoard {
+    pieces: [[u64; 6]; 2],
+    pub side_to_move: PieceColor,
+    castling: u8,
+    en_passant: Option<u8>,
+}
+
+impl BitBoard {
+    pub fn from_board(board: &Board) -> Result<BitBoard, BitBoardError> {
+        if board.ranks != 8 || board.files != 8 {
+            return Err(BitBoardError::UnsupportedSize);
+        }
+
+        let mut pieces = [[0; 6]; 2];
+        for color in [PieceColor::White, PieceColor::Black] {
+            for (loc, piece) in board.pieces_of_color(color) {
+                pieces[color_index(color)][piece_index(piece.piece_type)] |= 1 << square_of(loc);
+            }
+        }
+
+        let mut castling = 0;
+        for (color, kingside, right, rook_file) in [
+            (PieceColor::White, true, WHITE_KINGSIDE, 7),
+            (PieceColor::White, false, WHITE_QUEENSIDE, 0),
+            (PieceColor::Black, true, BLACK_KINGSIDE, 7),
+            (PieceColor::Black, false, BLACK_QUEENSIDE, 0),
+        ] {
+            match board.castling_rook_file(color, kingside) {
+                Some(file) if file == rook_file => castling |= right,
+                Some(_) => return Err(BitBoardError::UnsupportedCastling),
+                None => (),
+            }
+        }
+
+        Ok(BitBoard {
+            pieces,
+            side_to_move: board.side_to_move(),
+            castling,
+            en_passant: board.en_passant_target.map(square_of),
+        })
+    }
+
+    /// Builds a `Board` holding the same position. Which pieces have moved is inferred the
+    /// same way as `Board::from_fen`, and the clocks start fresh.
+    pub fn to_board(&self) -> Board {
+        let mut rows: Vec<String> = Vec::new();
+        for rank in (0..8).rev() {
+            let mut row = String::new();
+            let mut empty_squares = 0;
+            for file in 0..8 {
+                match self.piece_at(rank * 8 + file) {
+                    Some(piece) => {
+                        if empty_squares > 0 {
+                            row.push_str(&empty_squares.to_string());
+                            empty_squares = 0;
+                        }
+                        row.push(board_display::get_piece_char_cased(&piece));
+                    }
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0 {
+                row.push_str(&empty_squares.to_string());
+            }
+            rows.push(row);
+        }
+
+        let mut castling: String = [
+            (WHITE_KINGSIDE, 'K'),
+            (WHITE_QUEENSIDE, 'Q'),
+            (BLACK_KINGSIDE, 'k'),
+            (BLACK_QUEENSIDE, 'q'),
+        ]
+        .iter()
+        .filter(|(right, _)| self.castling & right != 0)
+        .map(|(_, c)| *c)
+        .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let active_color = match self.side_to_move {
+            PieceColor::White => "w",
+            PieceColor::Black => "b",
+        };
+        let en_passant = match self.en_passant {
+            Some(square) => PieceLoc::new(square / 8, square % 8).to_string(),
+            None => String::from("-"),
+        };
+
+        let fen = format!(
+            "{} {} {} {} 0 1",
+            rows.join("/"),
+            active_color,
+            castling,
+            en_passant
+        );
+        Board::from_fen(&fen).expect("A bitboard position should always be valid FEN")
+    }
+
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep, matching
+    /// `Board::perft` but much faster.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let mut nodes = 0;
+        for m in self.pseudo_legal_moves() {
+            let next = self.make_move(m);
+            if next.is_in_check(self.side_to_move) {
+                continue;
+            }
+            nodes += if depth == 1 { 1 } else { next.perft(depth - 1) };
+        }
+        nodes
+    }
+
+    /// Checks if any piece of color `by` attacks the square, whatever is standing on it.
+    pub fn is_square_attacked(&self, loc: PieceLoc, by: PieceColor) -> bool {
+        self.attacks_to(square_of(loc), by)
+    }
+
+    pub fn is_in_check(&self, color: PieceColor) -> bool {
+        let king = self.pieces[color_index(color)][piece_index(PieceType::King)];
+        king != 0 && self.attacks_to(king.trailing_zeros() as u8, color.flip())
+    }
+
+    fn occupied_by(&self, color: PieceColor) -> u64 {
+        self.pieces[color_index(color)]
+            .iter()
+            .fold(0, |acc, set| acc | set)
+    }
+
+    fn occupied(&self) -> u64 {
+        self.occupied_by(PieceColor::White) | self.occupied_by(PieceColor::Black)
+    }
+
+    fn piece_at(&self, square: u8) -> Option<Piece> {
+        let bit = 1 << square;
+        for color in [PieceColor::White, PieceColor::Black] {
+            for piece_type in PIECE_TYPES {
+                if self.pieces[color_index(color)][piece_index(piece_type)] & bit != 0 {
+                    return Some(Piece::new(piece_type, color));
+                }
+            }
+        }
+        None
+    }
+
+    // Looks outward from the square with each piece's own attack pattern, since a piece
+    // attacks the square exactly when the square would attack it back
+    fn attacks_to(&self, square: u8, by: PieceColor) -> bool {
+        let theirs = &self.pieces[color_index(by)];
+        let occupied = self.occupied();
+        let diagonal =
+            theirs[piece_index(PieceType::Bishop)] | theirs[piece_index(PieceType::Queen)];
+        let straight = theirs[piece_index(PieceType::Rook)] | theirs[piece_index(PieceType::Queen)];
+
+        pawn_attacks(1 << square, by.flip()) & theirs[piece_index(PieceType::Pawn)] != 0
+            || KNIGHT_ATTACKS[usize::from(square)] & theirs[piece_index(PieceType::Knight)] != 0
+            || KING_ATTACKS[usize::from(square)] & theirs[piece_index(PieceType::King)] != 0
+            || slider_attacks(square, occupied, &BISHOP_DIRECTIONS) & diagonal != 0
+            || slider_attacks(square, occupied, &ROOK_DIRECTIONS) & straight != 0
+    }
+
+    // Every move for the side to move, including those that leave its own king in check
+    fn pseudo_legal_moves(&self) -> Vec<BitMove> {
+        let color = self.side_to_move;
+        let ours = &self.pieces[color_index(color)];
+        let own = self.occupied_by(color);
+        let enemy = self.occupied_by(color.flip());
+        let occupied = own | enemy;
+        let mut moves = Vec::new();
+
+        let mut add = |from: u8, targets: u64| {
+            for to in squares_of(targets) {
+                moves.push(BitMove {
+                    from,
+                    to,
+                    promotion: None,
+                });
+            }
+        };
+
+        for from in squares_of(ours[piece_index(PieceType::Knight)]) {
+            add(from, KNIGHT_ATTACKS[usize::from(from)] & !own);
+        }
+        for from in squares_of(ours[piece_index(PieceType::Bishop)]) {
+            add(
+                from,
+                slider_attacks(from, occupied, &BISHOP_DIRECTIONS) & !own,
+            );
+        }
+        for from in squares_of(ours[piece_index(PieceType::Rook)]) {
+            add(
+                from,
+                slider_attacks(from, occupied, &ROOK_DIRECTIONS) & !own,
+            );
+        }
+        for from in squares_of(ours[piece_index(PieceType::Queen)]) {
+            let attacks = slider_attacks(from, occupied, &BISHOP_DIRECTIONS)
+                | slider_attacks(from, occupied, &ROOK_DIRECTIONS);
+            add(from, attacks & !own);
+        }
+        for from in squares_of(ours[piece_index(PieceType::King)]) {
+            add(from, KING_ATTACKS[usize::from(from)] & !own);
+        }
+
+        self.add_pawn_moves(&mut moves, occupied, enemy);
+        self.add_castling_moves(&mut moves, occupied);
+        moves
+    }
+
+    fn add_pawn_moves(&self, moves: &mut Vec<BitMove>, occupied: u64, enemy: u64) {
+        let color = self.side_to_move;
+        let (forward, home_rank, last_rank): (i8, u8, u64) = match color {
+            PieceColor::White => (8, 1, RANK_8),
+            PieceColor::Black => (-8, 6, RANK_1),
+        };
+        let capturable = enemy | self.en_passant.map_or(0, |square| 1 << square);
+
+        for from in squares_of(self.pieces[color_index(color)][piece_index(PieceType::Pawn)]) {
+            let mut targets = pawn_attacks(1 << from, color) & capturable;
+
+            let one_step = (from as i8 + forward) as u8;
+            if occupied & (1 << one_step) == 0 {
+                targets |= 1 << one_step;
+                let two_step = (one_step as i8 + forward) as u8;
+                if from / 8 == home_rank && occupied & (1 << two_step) == 0 {
+                    targets |= 1 << two_step;
+                }
+            }
+
+            for to in squares_of(targets) {
+                if last_rank & (1 << to) != 0 {
+                    for promotion in PROMOTION_PIECE_TYPES {
+                        moves.push(BitMove {
+                            from,
+                            to,
+                            promotion: Some(promotion),
+                        });
+                    }
+                } else {
+                    moves.push(BitMove {
+                        from,
+                        to,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+    }
+
+    // The king may not castle out of or through check; landing in check is caught later
+    // along with every other move
+    fn add_castling_moves(&self, moves: &mut Vec<BitMove>, occupied: u64) {
+        let color = self.side_to_move;
+        let (kingside, queenside, king_square) = match color {
+            PieceColor::White => (WHITE_KINGSIDE, WHITE_QUEENSIDE, 4),
+            PieceColor::Black => (BLACK_KINGSIDE, BLACK_QUEENSIDE, 60),
+        };
+        if self.castling & (kingside | queenside) == 0 || self.attacks_to(king_square, color.flip())
+        {
+            return;
+        }
+
+        // The squares between king and rook, and the square the king passes over
+        for (right, between, passed, to) in [
+            (
+                kingside,
+                0b11 << (king_square + 1),
+                king_square + 1,
+                king_square + 2,
+            ),
+            (
+                queenside,
+                0b111 << (king_square - 3),
+                king_square - 1,
+                king_square - 2,
+            ),
+        ] {
+            if self.castling & right != 0
+                && occupied & between == 0
+                && !self.attacks_to(passed, color.flip())
+            {
+                moves.push(BitMove {
+                    from: king_square,
+                    to,
+                    promotion: None,
+                });
+            }
+        }
+    }
+
+    fn make_move(&self, m: BitMove) -> BitBoard {
+        let mut next = *self;
+        let color = self.side_to_move;
+        let us = color_index(color);
+        let them = color_index(color.flip());
+        let from_bit: u64 = 1 << m.from;
+        let to_bit: u64 = 1 << m.to;
+
+        let moving = (0..6)
+            .find(|&i| self.pieces[us][i] & from_bit != 0)
+            .expect("A move should start from one of the mover's pieces");
+
+        for set in next.pieces[them].iter_mut() {
+            *set &= !to_bit;
+        }
+
+        let pawn = piece_index(PieceType::Pawn);
+        if moving == pawn && Some(m.to) == self.en_passant {
+            // The captured pawn sits behind the square the capturing pawn lands on
+            let victim = if color == PieceColor::White {
+                m.to - 8
+            } else {
+                m.to + 8
+            };
+            next.pieces[them][pawn] &= !(1 << victim);
+        }
+
+        next.pieces[us][moving] &= !from_bit;
+        match m.promotion {
+            Some(piece_type) => next.pieces[us][piece_index(piece_type)] |= to_bit,
+            None => next.pieces[us][moving] |= to_bit,
+        }
+
+        if moving == piece_index(PieceType::King) && m.from.abs_diff(m.to) == 2 {
+            let (rook_from, rook_to) = if m.to > m.from {
+                (m.from + 3, m.from + 1)
+            } else {
+                (m.from - 4, m.from - 1)
+            };
+            let rook = piece_index(PieceType::Rook);
+            next.pieces[us][rook] &= !(1 << rook_from);
+            next.pieces[us][rook] |= 1 << rook_to;
+        }
+
+        // Any move touching a king or corner rook square gives up the rights tied to it
+        for (square, rights) in [
+            (4, WHITE_KINGSIDE | WHITE_QUEENSIDE),
+            (7, WHITE_KINGSIDE),
+            (0, WHITE_QUEENSIDE),
+            (60, BLACK_KINGSIDE | BLACK_QUEENSIDE),
+            (63, BLACK_KINGSIDE),
+            (56, BLACK_QUEENSIDE),
+        ] {
+            if m.from == square || m.to == square {
+                next.castling &= !rights;
+            }
+        }
+
+        next.en_passant = if moving == pawn && m.from.abs_diff(m.to) == 16 {
+            Some((m.from + m.to) / 2)
+        } else {
+            None
+        };
+        next.side_to_move = color.flip();
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leaper_tables() {
+        // A knight in the corner reaches b3 and c2, a king on e4 has all eight neighbours
+        assert_eq!((1 << 17) | (1 << 10), KNIGHT_ATTACKS[0]);
+        assert_eq!(8, KING_ATTACKS[28].count_ones());
+    }
+
+    #[test]
+    fn test_round_trip_through_board() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        let bitboard = BitBoard::from_board(&board).unwrap();
+
+        assert_eq!(fen, bitboard.to_board().to_fen());
+    }
+
+    #[test]
+    fn test_from_board_rejects_other_sizes() {
+        assert_eq!(
+            BitBoardError::UnsupportedSize,
+            BitBoard::from_board(&Board::empty(6, 6)).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_is_square_attacked_matches_board() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let bitboard = BitBoard::from_board(&board).unwrap();
+
+        for rank in 0..8 {
+            for file in 0..8 {
+                let loc = PieceLoc::new(rank, file);
+                for color in [PieceColor::White, PieceColor::Black] {
+                    assert_eq!(
+                        board.is_square_attacked(loc, color),
+                        bitboard.is_square_attacked(loc, color),
+                        "{} attacked by {}",
+                        loc,
+                        color
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_perft_tricky_positions() {
+        // Castling, en passant, promotions and pins, from the Chess Programming Wiki
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let position_3 = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+        let position_4 = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
+        for (fen, expected) in [
+            (kiwipete, [48, 2039, 97862]),
+            (position_3, [14, 191, 2812]),
+            (position_4, [6, 264, 9467]),
+        ] {
+            let bitboard = BitBoard::from_board(&Board::from_fen(fen).unwrap()).unwrap();
+            for (depth, nodes) in (1..).zip(expected) {
+                assert_eq!(nodes, bitboard.perft(depth), "{} at depth {}", fen, depth);
+            }
+        }
+    }
+}
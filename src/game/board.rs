@@ -1,330 +1,2879 @@
 use core::fmt;
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use crate::game::moves::Move;
+use crate::game::moves::{self, Move};
 use crate::game::piece::{
-    piece_info::{PieceColor, PieceLoc, PieceType},
+    piece_info::{NotationError, PieceColor, PieceLoc, PieceType},
     Piece,
 };
+use crate::game::status::{DrawReason, GameStatus};
 
-use super::moves::move_checker::MoveType;
+use super::moves::move_checker::{self, MoveError, MoveType};
+
+pub mod epd;
+pub mod eval;
+pub mod fen;
+pub mod pgn;
+pub mod zobrist;
+
+// The most legal moves a cornered opponent may have before `stalemate_risk` warns about it
+const STALEMATE_RISK_MAX_MOVES: usize = 2;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Board {
     pub ranks: u8,
     pub files: u8,
     pub current_turn: PieceColor,
     pub move_list: Vec<Move>,
     pub board: Vec<Option<Piece>>,
-    pub graveyard: HashMap<PieceColor, HashMap<PieceType, u8>>,
+    // Each color's captured pieces, in the order they were taken
+    pub graveyard: HashMap<PieceColor, Vec<Piece>>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    // The square a pawn skipped over with a two-square move on the previous turn
+    pub en_passant_target: Option<PieceLoc>,
+    pub castling_rights: HashMap<PieceColor, CastlingRights>,
+    // The hash of the position before each move in the move list, for counting repetitions
+    position_history: Vec<u64>,
+    // The Zobrist hash of the current position, see `position_hash`
+    hash: u64,
+    // The board as it stood before the first move in the move list, which may have been
+    // loaded from FEN or set up by hand rather than being the standard setup. It is saved
+    // by the first move played and shared between copies of the board.
+    start: Option<Arc<Board>>,
+}
+
+/// The files of the rooks a side may still castle with. A right is lost for good once the
+/// king or that rook moves, or the rook is captured, even if the pieces later return.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub kingside: Option<u8>,
+    pub queenside: Option<u8>,
+}
+
+impl CastlingRights {
+    // Both rooks start in the corners
+    pub fn standard(files: u8) -> CastlingRights {
+        CastlingRights {
+            kingside: Some(files - 1),
+            queenside: Some(0),
+        }
+    }
+
+    pub fn none() -> CastlingRights {
+        CastlingRights {
+            kingside: None,
+            queenside: None,
+        }
+    }
+}
+
+/// How a move attacks the opposing king, which SAN marks with "+" or "#".
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CheckKind {
+    Check,
+    Checkmate,
+}
+
+/// A saved copy of a board's full state, including its move list and graveyard, which can
+/// later be handed back to `Board::restore` to roll back any number of moves.
+#[derive(Clone, Debug)]
+pub struct BoardSnapshot(Board);
+
+/// Everything that makes two positions the same for the rules: where each piece stands,
+/// the side to move, the castling rights and the en passant target. Unlike a `Board` it can
+/// key a `HashMap`, and unlike a hash of the position two different positions never share
+/// a key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionKey {
+    ranks: u8,
+    files: u8,
+    squares: Vec<Option<(PieceType, PieceColor)>>,
+    side_to_move: PieceColor,
+    // The castling rook files, kingside then queenside, for white then black
+    castling: [Option<u8>; 4],
+    en_passant_target: Option<PieceLoc>,
+}
+
+/// What `Board::unmake_move` needs to take back a move made with `Board::make_move`. The
+/// moving piece as it stood beforehand, with its old `has_moved` flag and type, and the
+/// captured piece both travel on the move itself, which stays in the move list until then.
+#[derive(Clone, Debug)]
+pub struct MoveUndo {
+    // A castled rook as it stood before the move, and the squares it moved from and to
+    rook: Option<(Piece, PieceLoc, PieceLoc)>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    en_passant_target: Option<PieceLoc>,
+    // White's rights, then black's
+    castling_rights: [CastlingRights; 2],
+    hash: u64,
 }
 
 impl Board {
     pub fn new() -> Board {
+        Board::with_size(8, 8)
+    }
+
+    /// Creates a board of the given size with the pieces set up as on a standard board,
+    /// scaled to fit: the king and queen in the middle of the back rank, rooks in the
+    /// corners, and knights and bishops between them. A 6x6 board gets the Los Alamos
+    /// setup of rook, knight, queen, king, knight, rook.
+    ///
+    /// Castling is only allowed on boards eight files wide, and the board needs at least
+    /// four ranks and two files to fit both sides' pieces.
+    pub fn with_size(ranks: u8, files: u8) -> Board {
+        let castling = match files {
+            8 => CastlingRights::standard(files),
+            _ => CastlingRights::none(),
+        };
         Board {
-            ranks: 8,
-            files: 8,
+            ranks,
+            files,
             current_turn: PieceColor::White,
             move_list: Vec::new(),
-            board: Board::generate_default_board(8, 8),
+            board: Board::generate_default_board(usize::from(ranks), usize::from(files)),
             graveyard: Board::generate_empty_graveyard(),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            en_passant_target: None,
+            castling_rights: HashMap::from([
+                (PieceColor::White, castling),
+                (PieceColor::Black, castling),
+            ]),
+            position_history: Vec::new(),
+            hash: 0,
+            start: None,
         }
+        .rehashed()
     }
 
-    fn update(
-        self,
-        board: Vec<Option<Piece>>,
-        move_list: Vec<Move>,
-        graveyard: HashMap<PieceColor, HashMap<PieceType, u8>>,
-    ) -> Board {
+    /// Creates a board of the given size with no pieces on it, ready to be filled in with
+    /// `with_piece`. White moves first, the clocks start from zero, and neither side may
+    /// castle until given the right with `with_castling_rights`.
+    pub fn empty(ranks: u8, files: u8) -> Board {
         Board {
-            board,
-            move_list,
-            graveyard,
-            current_turn: self.current_turn.flip(),
-            ..self
+            ranks,
+            files,
+            board: vec![None; usize::from(ranks) * usize::from(files)],
+            castling_rights: HashMap::from([
+                (PieceColor::White, CastlingRights::none()),
+                (PieceColor::Black, CastlingRights::none()),
+            ]),
+            ..Board::new()
         }
+        .rehashed()
     }
 
-    fn generate_default_board(ranks: usize, files: usize) -> Vec<Option<Piece>> {
-        let mut board: Vec<Option<Piece>> = vec![None; ranks * files];
+    fn rehashed(mut self) -> Board {
+        self.hash = self.compute_hash();
+        self
+    }
+
+    /// Places a piece on a square, replacing whatever was there.
+    pub fn with_piece(mut self, loc: PieceLoc, piece: Piece) -> Board {
+        self.set_piece(loc, Some(piece));
+        self
+    }
+
+    pub fn with_turn(mut self, color: PieceColor) -> Board {
+        self.edit_state(|board| board.current_turn = color);
+        self
+    }
+
+    pub fn with_clocks(mut self, halfmove_clock: u32, fullmove_number: u32) -> Board {
+        self.edit_state(|board| {
+            board.halfmove_clock = halfmove_clock;
+            board.fullmove_number = fullmove_number;
+        });
+        self
+    }
 
-        // Generate White Pieces
-        board[0] = Some(Piece::new(PieceType::Rook, PieceColor::White));
-        board[1] = Some(Piece::new(PieceType::Knight, PieceColor::White));
-        board[2] = Some(Piece::new(PieceType::Bishop, PieceColor::White));
-        board[3] = Some(Piece::new(PieceType::Queen, PieceColor::White));
-        board[4] = Some(Piece::new(PieceType::King, PieceColor::White));
-        board[5] = Some(Piece::new(PieceType::Bishop, PieceColor::White));
-        board[6] = Some(Piece::new(PieceType::Knight, PieceColor::White));
-        board[7] = Some(Piece::new(PieceType::Rook, PieceColor::White));
+    pub fn with_castling_rights(mut self, color: PieceColor, rights: CastlingRights) -> Board {
+        self.edit_state(|board| {
+            board.castling_rights.insert(color, rights);
+        });
+        self
+    }
+
+    // Changes the side to move, clocks or castling rights, keeping the hash in step.
+    // Outside of a move this sets up a new starting position.
+    fn edit_state(&mut self, edit: impl FnOnce(&mut Board)) {
+        self.hash ^= self.state_hash();
+        edit(self);
+        self.hash ^= self.state_hash();
+        if self.move_list.is_empty() {
+            self.start = None;
+        }
+    }
+
+    // Puts a piece on, or with None clears, a single square
+    pub fn set_piece(&mut self, loc: PieceLoc, piece: Option<Piece>) {
+        self.replace_piece(loc, piece);
+        if self.move_list.is_empty() {
+            self.start = None;
+        }
+    }
 
-        // Generate White Pawns
-        for file in 0..8 {
-            board[8 + file] = Some(Piece::new(PieceType::Pawn, PieceColor::White));
+    // Swaps the piece on a square, keeping the hash in step, and returns what was there
+    fn replace_piece(&mut self, loc: PieceLoc, piece: Option<Piece>) -> Option<Piece> {
+        let index = self.get_board_index_from_loc(loc);
+        let old = std::mem::replace(&mut self.board[index], piece);
+        for changed in [old, piece].into_iter().flatten() {
+            self.hash ^= zobrist::piece_key(index, changed);
         }
+        old
+    }
+
+    // Lays out the back ranks with the king and queen in the middle and rooks, knights and
+    // bishops filling out from the corners, so eight files give the standard setup. Pawns
+    // stand on the rank in front of each.
+    fn generate_default_board(ranks: usize, files: usize) -> Vec<Option<Piece>> {
+        let mut board: Vec<Option<Piece>> = vec![None; ranks * files];
 
-        // Generate Black Pawns
-        for file in 0..8 {
-            board[48 + file] = Some(Piece::new(PieceType::Pawn, PieceColor::Black));
+        let mut back_rank = vec![None; files];
+        back_rank[files / 2] = Some(PieceType::King);
+        if let Some(queen_file) = (files / 2).checked_sub(1) {
+            back_rank[queen_file] = Some(PieceType::Queen);
+        }
+        for offset in 0..files / 2 {
+            let piece_type = [PieceType::Rook, PieceType::Knight, PieceType::Bishop][offset % 3];
+            for file in [offset, files - 1 - offset] {
+                back_rank[file].get_or_insert(piece_type);
+            }
         }
 
-        // Generate Black Piecces
-        board[56] = Some(Piece::new(PieceType::Rook, PieceColor::Black));
-        board[57] = Some(Piece::new(PieceType::Knight, PieceColor::Black));
-        board[58] = Some(Piece::new(PieceType::Bishop, PieceColor::Black));
-        board[59] = Some(Piece::new(PieceType::Queen, PieceColor::Black));
-        board[60] = Some(Piece::new(PieceType::King, PieceColor::Black));
-        board[61] = Some(Piece::new(PieceType::Bishop, PieceColor::Black));
-        board[62] = Some(Piece::new(PieceType::Knight, PieceColor::Black));
-        board[63] = Some(Piece::new(PieceType::Rook, PieceColor::Black));
+        for (file, piece_type) in back_rank.into_iter().enumerate() {
+            let piece_type = piece_type.expect("Every back rank square is filled");
+            board[file] = Some(Piece::new(piece_type, PieceColor::White));
+            board[files + file] = Some(Piece::new(PieceType::Pawn, PieceColor::White));
+            board[(ranks - 2) * files + file] =
+                Some(Piece::new(PieceType::Pawn, PieceColor::Black));
+            board[(ranks - 1) * files + file] = Some(Piece::new(piece_type, PieceColor::Black));
+        }
 
         board
     }
 
-    fn generate_empty_graveyard() -> HashMap<PieceColor, HashMap<PieceType, u8>> {
+    fn generate_empty_graveyard() -> HashMap<PieceColor, Vec<Piece>> {
         HashMap::from([
-            (
-                PieceColor::White,
-                HashMap::from([
-                    (PieceType::Pawn, 0),
-                    (PieceType::Knight, 0),
-                    (PieceType::Bishop, 0),
-                    (PieceType::Rook, 0),
-                    (PieceType::Queen, 0),
-                ]),
-            ),
-            (
-                PieceColor::Black,
-                HashMap::from([
-                    (PieceType::Pawn, 0),
-                    (PieceType::Knight, 0),
-                    (PieceType::Bishop, 0),
-                    (PieceType::Rook, 0),
-                    (PieceType::Queen, 0),
-                ]),
-            ),
+            (PieceColor::White, Vec::new()),
+            (PieceColor::Black, Vec::new()),
         ])
     }
 
-    fn record_move(&mut self, new_move: &Move) -> Vec<Move> {
-        let mut new_move_list = self.move_list.clone();
-        new_move_list.push(new_move.clone());
-        new_move_list
-    }
-
-    fn handle_move_piece_to_graveyard(
-        &mut self,
-        m: &Move,
-    ) -> HashMap<PieceColor, HashMap<PieceType, u8>> {
-        if m.capturing {
-            match m.move_type {
-                MoveType::Normal | MoveType::EnPassant => {
-                    if let Some(captured_piece_idx) = self.get_captured_piece_idx(m) {
-                        if let Some(captured_piece) = self.board[captured_piece_idx] {
-                            let mut new_graveyard = self.graveyard.clone();
-                            let color_grave = new_graveyard
-                                .get_mut(&captured_piece.color)
-                                .expect("Didn't find color in graveyard");
-                            let piece_grave =
-                                color_grave.entry(captured_piece.piece_type).or_insert(1);
-                            *piece_grave += 1;
-
-                            self.board[captured_piece_idx] = None;
-                            return new_graveyard;
-                        } else {
-                            panic!("Somehow captured piece that didn't exist.");
-                        }
-                    }
-                }
-                _ => (),
-            }
+    fn handle_move_piece_to_graveyard(&mut self, m: &Move) {
+        if let Some(captured_piece) = m.captured {
+            self.graveyard
+                .entry(captured_piece.color)
+                .or_default()
+                .push(captured_piece);
         }
-        // Fallback to returning the old graveyard if no capture happened
-        self.graveyard.clone()
     }
 
-    fn get_captured_piece_idx(&self, m: &Move) -> Option<usize> {
+    fn get_captured_piece_loc(&self, m: &Move) -> Option<PieceLoc> {
         match m.move_type {
-            MoveType::Normal => Some(self.get_board_index_from_loc(m.end_pos)),
-            MoveType::EnPassant => {
-                match m.piece.color {
-                    PieceColor::White => Some(self.get_board_index_from_loc(PieceLoc::new(
-                        m.end_pos.rank - 1,
-                        m.end_pos.file,
-                    ))),
-                    PieceColor::Black => Some(self.get_board_index_from_loc(PieceLoc::new(
-                        m.end_pos.rank + 1,
-                        m.end_pos.file,
-                    ))),
-                }
+            MoveType::Normal => Some(m.end_pos),
+            MoveType::EnPassant => Some(move_checker::en_passant_victim(m.end_pos, m.piece.color)),
+            // Castling never captures, even though the king lands beside its own rook
+            MoveType::Castling => None,
+        }
+    }
+
+    /// Fills in the piece a capturing move takes and the square it is taken from, if the
+    /// move doesn't already carry them.
+    ///
+    /// Fails if there is nothing to capture on that square, which can only happen for a
+    /// move that was never legal here, or after a FEN naming an en passant target without
+    /// the pawn that skipped over it.
+    pub fn with_captured_piece(&self, mut m: Move) -> Result<Move, MoveError> {
+        if m.capturing && m.captured.is_none() {
+            if let Some(loc) = self.get_captured_piece_loc(&m) {
+                let captured_piece = self
+                    .get_piece_at_location(loc)
+                    .ok_or(MoveError::NoPieceToCapture { at: loc })?;
+                m.captured = Some(captured_piece);
+                m.captured_pos = Some(loc);
             }
-            MoveType::Castling => panic!("Cannot castle into a capture."),
         }
+        Ok(m)
     }
 
-    fn handle_moving_piece(&self, m: &Move) -> Vec<Option<Piece>> {
-        let mut new_board = self.board.clone();
-        let mut selected_piece = m.piece;
+    // Moves the piece, and for castling its rook, returning the rook as it was beforehand
+    fn handle_moving_piece(&mut self, m: &Move) -> Option<(Piece, PieceLoc, PieceLoc)> {
+        if m.move_type == MoveType::Castling {
+            return Some(self.handle_castling(m));
+        }
+
+        let mut selected_piece = self
+            .replace_piece(m.start_pos, None)
+            .expect("Moved a piece that didn't exist.");
 
-        let start_board_idx = self.get_board_index_from_loc(m.start_pos);
-        let end_board_idx = self.get_board_index_from_loc(m.end_pos);
+        // Clear the captured piece first, as en passant takes it from a different square
+        if let Some(captured_pos) = m.captured_pos {
+            self.replace_piece(captured_pos, None);
+        }
 
         selected_piece.has_moved = true;
-        new_board[end_board_idx] = Some(selected_piece);
-        new_board[start_board_idx] = None;
-
-        // Update the moved piece's has_moved flag
-        if let Some(_piece) = new_board[end_board_idx] {
-            new_board[end_board_idx].unwrap().has_moved = true;
+        if let Some(promotion) = m.promotion {
+            selected_piece.piece_type = promotion;
         }
-        new_board
+        self.replace_piece(m.end_pos, Some(selected_piece));
+        None
     }
 
-    // Accepts a move, which has been verified to be a valid move by the Move::new() constructor
-    pub fn move_piece(mut self, new_move: Move) -> Board {
-        let new_move_list = self.record_move(&new_move);
-        let new_board = self.handle_moving_piece(&new_move);
-        let new_graveyard = self.handle_move_piece_to_graveyard(&new_move);
+    // Both the king and the rook are lifted before either is put down, since in Chess960
+    // the king may land where the rook started or the other way around
+    fn handle_castling(&mut self, m: &Move) -> (Piece, PieceLoc, PieceLoc) {
+        let kingside = m.end_pos.file > m.start_pos.file;
+        let rook_file = self
+            .castling_rook_file(m.piece.color, kingside)
+            .expect("Castled without the right to");
+        let (king_to, rook_to) = self.castling_destinations(m.start_pos.rank, kingside);
+        let rook_start = PieceLoc::new(m.start_pos.rank, rook_file);
+
+        let king = self
+            .replace_piece(m.start_pos, None)
+            .expect("Moved a piece that didn't exist.");
+        let rook = self
+            .replace_piece(rook_start, None)
+            .expect("Castled without a rook.");
 
-        self.update(new_board, new_move_list, new_graveyard)
+        self.replace_piece(
+            rook_to,
+            Some(Piece {
+                has_moved: true,
+                ..rook
+            }),
+        );
+        self.replace_piece(
+            king_to,
+            Some(Piece {
+                has_moved: true,
+                ..king
+            }),
+        );
+        (rook, rook_start, rook_to)
     }
 
-    pub fn get_previous_move(&self) -> Option<Move> {
-        self.move_list.last().cloned()
+    /// Plays a move, returning the board after it. The move is checked against this board
+    /// first, so a move built for some other position is rejected rather than applied.
+    pub fn move_piece(self, new_move: Move) -> Result<Board, MoveError> {
+        if self.get_piece_at_location(new_move.start_pos) != Some(new_move.piece) {
+            return Err(MoveError::PieceNotAtStart {
+                start: new_move.start_pos,
+            });
+        }
+        let checked_move = Move::new_with_promotion(
+            &self,
+            &new_move.piece,
+            &new_move.start_pos,
+            &new_move.end_pos,
+            new_move.promotion,
+        )?;
+        Ok(self.apply_move(checked_move))
     }
 
-    pub fn get_piece_at_location(&self, loc: PieceLoc) -> Option<Piece> {
-        let board_index = self.get_board_index_from_loc(loc);
-        self.board.get(board_index).copied().unwrap_or_else(|| None)
+    /// Returns the board as it would be after the move, leaving this one untouched. The
+    /// move is checked just as `move_piece` checks it.
+    pub fn preview_move(&self, m: &Move) -> Result<Board, MoveError> {
+        self.clone().move_piece(m.clone())
     }
 
-    fn get_board_index_from_loc(&self, loc: PieceLoc) -> usize {
-        ((loc.rank * self.ranks) + loc.file).into()
+    /// Plays the moves in order from the standard starting position, checking each one as
+    /// `move_piece` does. An illegal move stops the replay, returning its index in `moves`
+    /// along with why it was rejected.
+    pub fn replay(moves: &[Move]) -> Result<Board, (usize, MoveError)> {
+        moves
+            .iter()
+            .enumerate()
+            .try_fold(Board::new(), |board, (i, m)| {
+                board.move_piece(m.clone()).map_err(|e| (i, e))
+            })
     }
-}
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut output: String = "".to_string();
-        for rank in self.board.chunks(self.ranks.into()).rev() {
-            for square in rank {
-                let display_char;
-                match square {
-                    Some(piece) => display_char = board_display::get_piece_display(piece, false),
-                    None => display_char = '.',
-                }
+    // Accepts a move, which has been verified to be a valid move by the Move::new() constructor
+    pub(crate) fn apply_move(mut self, new_move: Move) -> Board {
+        self.make_move(new_move);
+        self
+    }
 
-                output.push(display_char);
-                output.push(' ');
+    /// Plays a move in place, returning what `unmake_move` needs to take it back. Unlike
+    /// `move_piece` the move isn't checked, so it must come from this board's legal moves.
+    ///
+    /// Nothing but the changed squares is written and the position hash is updated to
+    /// match, which makes this the cheapest way for a search to walk the move tree. Only
+    /// the first move from a position with no move list copies the board, saving it as
+    /// the `start_position`.
+    pub fn make_move(&mut self, new_move: Move) -> MoveUndo {
+        // An unchecked move claiming a capture on an empty square simply captures nothing
+        let mut new_move = self
+            .with_captured_piece(new_move.clone())
+            .unwrap_or(new_move);
+        // The move records the piece exactly as it stands, so taking it back restores the
+        // piece's has_moved flag rather than whatever the caller's copy claimed
+        new_move.piece = self
+            .get_piece_at_location(new_move.start_pos)
+            .expect("Moved a piece that didn't exist.");
+        if self.move_list.is_empty() && self.start.is_none() {
+            self.start = Some(Arc::new(self.clone()));
+        }
+        self.position_history.push(self.hash);
+        let mut undo = MoveUndo {
+            rook: None,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            en_passant_target: self.en_passant_target,
+            castling_rights: [PieceColor::White, PieceColor::Black]
+                .map(|color| self.castling_rights_of(color)),
+            hash: self.hash,
+        };
+        // The side to move, castling rights and en passant target all change below
+        self.hash ^= self.state_hash();
+
+        undo.rook = self.handle_moving_piece(&new_move);
+        self.handle_move_piece_to_graveyard(&new_move);
+        self.handle_castling_rights(&new_move);
+
+        // Any pawn move or capture is irreversible, restarting the count toward the fifty-move rule
+        if new_move.piece.piece_type == PieceType::Pawn || new_move.capturing {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        // A pawn's two-square move leaves the skipped square open to en passant for one turn
+        self.en_passant_target = if new_move.piece.piece_type == PieceType::Pawn
+            && new_move.start_pos.rank.abs_diff(new_move.end_pos.rank) == 2
+        {
+            Some(PieceLoc::new(
+                (new_move.start_pos.rank + new_move.end_pos.rank) / 2,
+                new_move.start_pos.file,
+            ))
+        } else {
+            None
+        };
+
+        // The move number only advances once black has replied
+        if self.current_turn == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+        self.current_turn = self.current_turn.flip();
+        self.hash ^= self.state_hash();
+        self.move_list.push(new_move);
+        undo
+    }
+
+    /// Takes back the last move, which must have been made with the `make_move` call that
+    /// returned `undo`.
+    pub fn unmake_move(&mut self, undo: MoveUndo) {
+        let m = self.move_list.pop().expect("No move to take back.");
+        self.position_history.pop();
+
+        if let Some((rook, rook_start, rook_end)) = undo.rook {
+            let kingside = m.end_pos.file > m.start_pos.file;
+            let (king_end, _) = self.castling_destinations(m.start_pos.rank, kingside);
+            for loc in [king_end, rook_end] {
+                let idx = self.get_board_index_from_loc(loc);
+                self.board[idx] = None;
             }
-            output.push('\n')
+            let rook_start_idx = self.get_board_index_from_loc(rook_start);
+            self.board[rook_start_idx] = Some(rook);
+        } else {
+            let end_idx = self.get_board_index_from_loc(m.end_pos);
+            self.board[end_idx] = None;
         }
+        let start_idx = self.get_board_index_from_loc(m.start_pos);
+        self.board[start_idx] = Some(m.piece);
 
-        write!(
-            f,
-            "{}\n\n{}\n\n{}",
-            output,
-            board_display::get_movelist_display(self),
-            board_display::get_graveyard_display(self),
+        if let (Some(captured), Some(captured_pos)) = (m.captured, m.captured_pos) {
+            let captured_idx = self.get_board_index_from_loc(captured_pos);
+            self.board[captured_idx] = Some(captured);
+            // Moves are taken back in reverse, so this was the latest capture of its color
+            if let Some(grave) = self.graveyard.get_mut(&captured.color) {
+                grave.pop();
+            }
+        }
+
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.en_passant_target = undo.en_passant_target;
+        let [white_rights, black_rights] = undo.castling_rights;
+        self.castling_rights.insert(PieceColor::White, white_rights);
+        self.castling_rights.insert(PieceColor::Black, black_rights);
+        self.current_turn = self.current_turn.flip();
+        self.hash = undo.hash;
+    }
+
+    fn castling_rights_of(&self, color: PieceColor) -> CastlingRights {
+        self.castling_rights
+            .get(&color)
+            .copied()
+            .unwrap_or(CastlingRights::none())
+    }
+
+    /// The file of the rook `color` may castle with on the given side, if it still has the
+    /// right to castle there.
+    pub fn castling_rook_file(&self, color: PieceColor, kingside: bool) -> Option<u8> {
+        let rights = self.castling_rights.get(&color)?;
+        if kingside {
+            rights.kingside
+        } else {
+            rights.queenside
+        }
+    }
+
+    /// Where the king and the rook end up when castling on the given side, wherever they
+    /// started: the g and f files kingside and the c and d files queenside, as in Chess960.
+    pub fn castling_destinations(&self, rank: u8, kingside: bool) -> (PieceLoc, PieceLoc) {
+        let (king_file, rook_file) = if kingside {
+            (self.files.saturating_sub(2), self.files.saturating_sub(3))
+        } else {
+            (2, 3)
+        };
+        (
+            PieceLoc::new(rank, king_file),
+            PieceLoc::new(rank, rook_file),
         )
     }
-}
 
-pub mod board_display {
-    // List of all valid alpha representation of ranks
-    static ALPHA_RANKS_UPPER: [char; 8] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H'];
+    pub(crate) fn back_rank(&self, color: PieceColor) -> u8 {
+        match color {
+            PieceColor::White => 0,
+            PieceColor::Black => self.ranks - 1,
+        }
+    }
 
-    use super::Board;
-    use crate::game::piece::{
-        piece_info::{PieceColor, PieceType},
-        Piece,
-    };
+    // Moving the king gives up both rights, while moving a rook or having it captured
+    // gives up only the right to castle with that rook
+    fn handle_castling_rights(&mut self, m: &Move) {
+        for color in [PieceColor::White, PieceColor::Black] {
+            let back_rank = self.back_rank(color);
+            let Some(rights) = self.castling_rights.get_mut(&color) else {
+                continue;
+            };
+            if m.piece.piece_type == PieceType::King && m.piece.color == color {
+                *rights = CastlingRights::none();
+                continue;
+            }
 
-    pub fn get_graveyard_display(board: &Board) -> String {
-        let mut output: String = String::from("Graveyard:");
-        let mut piece_display: [String; 2] = [
-            String::from("\n\tWhite pieces:"),
-            String::from("\n\tBlack pieces:"),
-        ];
-        for (color, piece_type) in &board.graveyard {
-            let display_index;
-            match color {
-                PieceColor::White => display_index = 0,
-                PieceColor::Black => display_index = 1,
+            let touches = |file: u8| {
+                let rook_loc = PieceLoc::new(back_rank, file);
+                m.start_pos == rook_loc || m.end_pos == rook_loc
             };
-            let mut found_captured_of_color = false;
-            for (p_type, &captured) in piece_type {
-                if captured > 0 {
-                    piece_display[display_index]
-                        .push_str(format!("\n\t\t{}x {}", captured, p_type).as_str());
-                    found_captured_of_color = true;
-                }
+            if rights.kingside.is_some_and(touches) {
+                rights.kingside = None;
             }
-            if !found_captured_of_color {
-                piece_display[display_index].push_str("\n\t\tNo pieces have been captured yet.");
+            if rights.queenside.is_some_and(touches) {
+                rights.queenside = None;
             }
         }
-        piece_display.iter().for_each(|color| {
-            output.push_str(color.as_str());
-        });
-        output
     }
 
-    pub fn get_movelist_display(board: &Board) -> String {
-        let mut output: String = String::from("Moves:");
+    /// Whether both boards hold the same position: the same pieces on the same squares,
+    /// the same side to move, and the same castling and en passant rights.
+    ///
+    /// How each board got there doesn't matter, so the move lists, graveyards, clocks and
+    /// whether pieces have moved are all ignored and transpositions compare equal.
+    pub fn same_position(&self, other: &Board) -> bool {
+        self.position_key() == other.position_key()
+    }
 
-        let mut turn = 1;
-        let mut white_turn: bool = true;
-        board.move_list.iter().for_each(|_move| {
-            if white_turn {
-                output.push('\n');
-                output.push_str(format!("{}. ", turn).as_str());
-                white_turn = false;
-            } else {
-                turn += 1;
-                white_turn = true;
-            }
-            output.push_str(format!("{} ", &_move.get_move_display()).as_str());
-        });
-        output
+    /// The key of this position, which is equal for any two boards `same_position` says
+    /// are the same, so positions can be counted or looked up in a `HashMap`.
+    pub fn position_key(&self) -> PositionKey {
+        PositionKey {
+            ranks: self.ranks,
+            files: self.files,
+            squares: self
+                .board
+                .iter()
+                .map(|square| square.map(|piece| (piece.piece_type, piece.color)))
+                .collect(),
+            side_to_move: self.current_turn,
+            castling: [
+                self.castling_rook_file(PieceColor::White, true),
+                self.castling_rook_file(PieceColor::White, false),
+                self.castling_rook_file(PieceColor::Black, true),
+                self.castling_rook_file(PieceColor::Black, false),
+            ],
+            en_passant_target: self.en_passant_target,
+        }
     }
 
-    pub fn get_piece_display(piece: &Piece, pawn_blank: bool) -> char {
-        match piece.piece_type {
-            PieceType::Pawn => {
-                if pawn_blank {
-                    return ' ';
+    pub fn snapshot(&self) -> BoardSnapshot {
+        BoardSnapshot(self.clone())
+    }
+
+    pub fn restore(&mut self, snap: BoardSnapshot) {
+        *self = snap.0;
+    }
+
+    /// The color whose turn it is.
+    pub fn side_to_move(&self) -> PieceColor {
+        self.current_turn
+    }
+
+    /// The number of the current full move, as in FEN: it starts at 1 and goes up once
+    /// black has replied.
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// The number of halfmoves since the last capture or pawn move, counting toward the
+    /// fifty-move rule.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn get_previous_move(&self) -> Option<Move> {
+        self.move_list.last().cloned()
+    }
+
+    /// The position the move list was played from: the board as it stood before the first
+    /// move, whether that was the standard setup, a FEN or pieces placed by hand. With no
+    /// moves played yet, that is this board itself.
+    pub fn start_position(&self) -> Board {
+        match &self.start {
+            Some(start) if !self.move_list.is_empty() => Board::clone(start),
+            _ => Board {
+                start: None,
+                ..self.clone()
+            },
+        }
+    }
+
+    /// Every move played so far in SAN, written as it was at the time it was played, i.e.
+    /// `["e4", "e5", "Nf3", "Nc6", "Bb5"]`.
+    ///
+    /// SAN depends on the position each move was played from, for disambiguation and the
+    /// check or mate suffix, so the move list is replayed from `start_position` to render it.
+    pub fn san_history(&self) -> Vec<String> {
+        let mut replay = self.start_position();
+        self.move_list
+            .iter()
+            .map(|m| {
+                let san = m.to_san(&replay);
+                replay.make_move(m.clone());
+                san
+            })
+            .collect()
+    }
+
+    /// Pairs each move of `san_history` with the move number written before it: "12."
+    /// before white's moves, "12..." before a first move by black, and nothing before
+    /// black's other moves. Numbering carries on from the fullmove number of
+    /// `start_position`.
+    pub fn numbered_san_history(&self) -> Vec<(Option<String>, String)> {
+        let start = self.start_position();
+        // Counted in plies from white's move of the starting move number
+        let black_first = start.side_to_move() == PieceColor::Black;
+        self.san_history()
+            .into_iter()
+            .enumerate()
+            .map(|(i, san)| {
+                let ply = i + usize::from(black_first);
+                let move_number = start.fullmove_number() as usize + ply / 2;
+                let label = if ply % 2 == 0 {
+                    Some(format!("{}.", move_number))
+                } else if i == 0 {
+                    Some(format!("{}...", move_number))
                 } else {
-                    return 'P';
-                }
-            }
-            PieceType::Knight => return 'N',
-            PieceType::Bishop => return 'B',
-            PieceType::Rook => return 'R',
-            PieceType::Queen => return 'Q',
-            PieceType::King => return 'K',
+                    None
+                };
+                (label, san)
+            })
+            .collect()
+    }
+
+    /// Reads a square of this board written as its file letter and rank number, i.e. "e4".
+    pub fn parse_square(&self, notation: &str) -> Result<PieceLoc, NotationError> {
+        PieceLoc::from_notation_on(notation, self.ranks, self.files)
+    }
+
+    pub fn get_piece_at_location(&self, loc: PieceLoc) -> Option<Piece> {
+        let board_index = self.get_board_index_from_loc(loc);
+        self.board.get(board_index).copied().flatten()
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        moves::generate_legal_moves(self)
+    }
+
+    /// The legal moves of the piece on `loc`, which is empty if the square is empty or
+    /// holds a piece of the side not to move.
+    pub fn legal_moves_for(&self, loc: PieceLoc) -> Vec<Move> {
+        match self.get_piece_at_location(loc) {
+            Some(piece) if piece.color == self.current_turn => self
+                .legal_moves()
+                .into_iter()
+                .filter(|m| m.start_pos == loc)
+                .collect(),
+            _ => Vec::new(),
         }
     }
 
-    pub fn convert_rank_alpha_to_numeric(rank: char) -> Option<u8> {
-        let rank = rank.clone().to_ascii_uppercase();
+    pub fn has_legal_move(&self) -> bool {
+        !self.legal_moves().is_empty()
+    }
 
-        if ALPHA_RANKS_UPPER.contains(&rank) {
-            match ALPHA_RANKS_UPPER.iter().position(|c| c == &rank) {
-                Some(i) => return Some(i as u8),
-                None => return None,
-            };
+    pub fn legal_move_count(&self) -> usize {
+        self.legal_moves().len()
+    }
+
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep, for checking the move
+    /// generator against known reference counts.
+    pub fn perft(&self, depth: u32) -> u64 {
+        self.clone().perft_in_place(depth)
+    }
+
+    // Walks the move tree on this one board, leaving it as it found it
+    fn perft_in_place(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
         }
-        None
+
+        let legal_moves = self.legal_moves();
+        // The leaves don't need to be played out to be counted
+        if depth == 1 {
+            return legal_moves.len() as u64;
+        }
+
+        legal_moves
+            .into_iter()
+            .map(|m| {
+                let undo = self.make_move(m);
+                let nodes = self.perft_in_place(depth - 1);
+                self.unmake_move(undo);
+                nodes
+            })
+            .sum()
     }
 
-    pub fn convert_rank_numeric_to_alpha(rank: u8) -> Option<char> {
-        let rank = usize::from(rank);
-        if rank < ALPHA_RANKS_UPPER.len() {
-            ALPHA_RANKS_UPPER.get(rank).copied()
+    /// Splits the perft count by root move, pairing each move in SAN with the number of
+    /// leaf nodes beneath it. Sorted by move so it can be diffed against other engines.
+    pub fn perft_divide(&self, depth: u32) -> Vec<(String, u64)> {
+        let mut board = self.clone();
+        let mut divide: Vec<(String, u64)> = self
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let san = m.to_san(self);
+                let undo = board.make_move(m);
+                let nodes = board.perft_in_place(depth.saturating_sub(1));
+                board.unmake_move(undo);
+                (san, nodes)
+            })
+            .collect();
+
+        divide.sort();
+        divide
+    }
+
+    // Fifty moves by each player without a pawn move or capture draws the game
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    // Seventy-five moves by each player ends the game even if neither side claims the draw
+    pub fn is_seventy_five_move_draw(&self) -> bool {
+        self.halfmove_clock >= 150
+    }
+
+    /// How many times the current position has occurred in this game, counting this one.
+    /// Positions from before a board was loaded from FEN are unknown, so aren't counted.
+    pub fn repetition_count(&self) -> usize {
+        // Nothing from before the last pawn move or capture can come round again
+        let earlier = self
+            .position_history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .filter(|&&past| past == self.hash)
+            .count();
+        earlier + 1
+    }
+
+    // The same position three times lets either player claim a draw
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    // The same position five times ends the game even if neither side claims the draw
+    pub fn is_fivefold_repetition(&self) -> bool {
+        self.repetition_count() >= 5
+    }
+
+    /// Reports whether the game is over, judged from the side to move: with no legal moves
+    /// they have been checkmated if their king is attacked, and stalemated otherwise.
+    ///
+    /// A checkmate delivered on the fiftieth move still stands over the fifty-move draw.
+    /// When a position is drawn for several reasons, one that ends the game at once is
+    /// reported over one that would have to be claimed.
+    pub fn game_status(&self) -> GameStatus {
+        if !self.has_legal_move() {
+            return if self.is_in_check(self.current_turn) {
+                GameStatus::Checkmate {
+                    winner: self.current_turn.flip(),
+                }
+            } else {
+                GameStatus::draw(DrawReason::Stalemate)
+            };
+        }
+
+        let repetitions = self.repetition_count();
+        let reason = if repetitions >= 5 {
+            DrawReason::FivefoldRepetition
+        } else if self.is_seventy_five_move_draw() {
+            DrawReason::SeventyFiveMoveRule
+        } else if self.is_dead_position() {
+            DrawReason::InsufficientMaterial
+        } else if self.is_fifty_move_draw() {
+            DrawReason::FiftyMoveRule
+        } else if repetitions >= 3 {
+            DrawReason::ThreefoldRepetition
         } else {
+            return GameStatus::Ongoing;
+        };
+        GameStatus::draw(reason)
+    }
+
+    /// Warns the side to move that the opponent is down to a lone (or nearly lone) king with
+    /// almost no legal moves left, where a careless quiet move could throw away the win by
+    /// stalemate.
+    pub fn stalemate_risk(&self) -> bool {
+        let opponent = self.current_turn.flip();
+        let opponent_non_king_pieces = self
+            .board
+            .iter()
+            .flatten()
+            .filter(|piece| piece.color == opponent && piece.piece_type != PieceType::King)
+            .count();
+        if opponent_non_king_pieces > 1 {
+            return false;
+        }
+
+        self.mobility(opponent) <= STALEMATE_RISK_MAX_MOVES
+    }
+
+    /// Counts the legal moves `color` would have if it were their turn in this position.
+    pub fn mobility(&self, color: PieceColor) -> usize {
+        if color == self.current_turn {
+            return self.legal_move_count();
+        }
+
+        // Play it out on a copy, where the pawn that just moved two squares can no longer
+        // be taken en passant by the side that would have had to reply at once
+        let hypothetical = Board {
+            current_turn: color,
+            en_passant_target: None,
+            ..self.clone()
+        };
+        hypothetical.legal_move_count()
+    }
+
+    pub fn is_in_check(&self, color: PieceColor) -> bool {
+        match self.find_king(color) {
+            Some(king_loc) => self.is_square_attacked(king_loc, color.flip()),
+            None => false,
+        }
+    }
+
+    /// The squares of every piece giving check to `color`'s king: none when it isn't in
+    /// check, and two in a double check.
+    pub fn checkers(&self, color: PieceColor) -> Vec<PieceLoc> {
+        match self.find_king(color) {
+            Some(king_loc) => self.attackers_of(king_loc, color.flip()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether playing `m` would put the opponent in check, including checkmate.
+    pub fn move_gives_check(&self, m: &Move) -> bool {
+        self.move_check_kind(m).is_some()
+    }
+
+    /// Whether playing `m` would put the opponent in check or checkmate, or None if it
+    /// would leave their king alone.
+    pub fn move_check_kind(&self, m: &Move) -> Option<CheckKind> {
+        let next_board = self.clone().apply_move(m.clone());
+        if !next_board.is_in_check(next_board.current_turn) {
             None
+        } else if next_board.has_legal_move() {
+            Some(CheckKind::Check)
+        } else {
+            Some(CheckKind::Checkmate)
+        }
+    }
+
+    /// Finds the square of the given color's king, or None if the board has no such king.
+    pub fn find_king(&self, color: PieceColor) -> Option<PieceLoc> {
+        self.pieces_of_color(color)
+            .find(|(_, piece)| piece.piece_type == PieceType::King)
+            .map(|(loc, _)| loc)
+    }
+
+    /// Finds every piece of the given color that is pinned to its own king, paired with
+    /// the square of the enemy slider pinning it.
+    ///
+    /// A piece is pinned when it is the only thing standing between its king and an enemy
+    /// rook, bishop or queen that moves along that line, so moving it off the line would
+    /// expose the king.
+    pub fn pinned_pieces(&self, color: PieceColor) -> Vec<(PieceLoc, PieceLoc)> {
+        let king_loc = match self.find_king(color) {
+            Some(loc) => loc,
+            None => return Vec::new(),
+        };
+
+        let mut pins = Vec::new();
+        for (rank_step, file_step) in [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ] {
+            let diagonal = rank_step != 0 && file_step != 0;
+            let mut candidate = None;
+            let mut loc = king_loc;
+
+            while let Some(next) = self.step(loc, rank_step, file_step) {
+                loc = next;
+                let piece = match self.get_piece_at_location(loc) {
+                    Some(piece) => piece,
+                    None => continue,
+                };
+
+                if piece.color == color {
+                    // A second piece of our own on the line means neither is pinned
+                    if candidate.is_some() {
+                        break;
+                    }
+                    candidate = Some(loc);
+                    continue;
+                }
+
+                let pins_along_line = match piece.piece_type {
+                    PieceType::Queen => true,
+                    PieceType::Rook => !diagonal,
+                    PieceType::Bishop => diagonal,
+                    _ => false,
+                };
+                if let (Some(pinned), true) = (candidate, pins_along_line) {
+                    pins.push((pinned, loc));
+                }
+                break;
+            }
         }
+        pins
+    }
+
+    // The square one step away in the given direction, or None if it is off the board
+    fn step(&self, loc: PieceLoc, rank_step: i8, file_step: i8) -> Option<PieceLoc> {
+        let rank = loc.rank.checked_add_signed(rank_step)?;
+        let file = loc.file.checked_add_signed(file_step)?;
+        (rank < self.ranks && file < self.files).then(|| PieceLoc::new(rank, file))
+    }
+
+    /// Whether any piece of color `by` attacks `loc`, regardless of what occupies it.
+    ///
+    /// Pawns attack diagonally forward rather than along their pushes, and sliding pieces
+    /// are blocked by anything in their path.
+    pub fn is_square_attacked(&self, loc: PieceLoc, by: PieceColor) -> bool {
+        self.pieces_of_color(by).any(|(attacker_loc, piece)| {
+            move_checker::attacks_square(self, &piece, &attacker_loc, &loc)
+        })
+    }
+
+    /// Every square from which a piece of color `by` attacks `loc`, by the same rules as
+    /// `is_square_attacked`.
+    ///
+    /// Only direct attacks count. X-rays are left out, so a rook standing behind another
+    /// rook or a queen on the same file isn't listed until the piece in front has moved.
+    pub fn attackers_of(&self, loc: PieceLoc, by: PieceColor) -> Vec<PieceLoc> {
+        self.pieces_of_color(by)
+            .filter(|(attacker_loc, piece)| {
+                move_checker::attacks_square(self, piece, attacker_loc, &loc)
+            })
+            .map(|(attacker_loc, _)| attacker_loc)
+            .collect()
+    }
+
+    /// Draws the board with Unicode chess figurines, which tell the colors apart by glyph.
+    pub fn to_unicode_string(&self) -> String {
+        board_display::get_grid_display(self, PieceColor::White, board_display::get_piece_unicode)
+    }
+
+    /// Draws the board as a bordered ASCII diagram for sharing, with every square boxed in
+    /// by `+---+` lines and the ranks and files labelled, as chess tutorials often do.
+    pub fn to_diagram(&self) -> String {
+        board_display::get_diagram_display(self)
+    }
+
+    /// Draws the board for a terminal, using ANSI escape codes to shade the light and dark
+    /// squares and to color each side's pieces. `Display` never emits escape codes.
+    pub fn to_ansi_string(&self) -> String {
+        board_display::get_ansi_grid_display(self)
+    }
+
+    /// Renders the board as `Display` does, but seen from `perspective`'s side of the
+    /// board, so that player's pieces are drawn at the bottom.
+    pub fn to_string_oriented(&self, perspective: PieceColor) -> String {
+        format!(
+            "{}\n\n{}\n{}\n\n{}",
+            board_display::get_grid_display(self, perspective, board_display::get_piece_char_cased),
+            board_display::get_movelist_display(self),
+            board_display::get_halfmove_clock_display(self),
+            board_display::get_graveyard_display(self),
+        )
+    }
+
+    /// Draws just the grid from `perspective`'s side, marking the `highlighted` squares so a
+    /// player can see where a selected piece is able to go.
+    pub fn to_string_highlighted(
+        &self,
+        perspective: PieceColor,
+        highlighted: &[PieceLoc],
+    ) -> String {
+        board_display::get_highlighted_grid_display(
+            self,
+            perspective,
+            board_display::get_piece_char_cased,
+            highlighted,
+        )
+    }
+
+    /// The given color's pieces that have been captured, in the order they were taken.
+    pub fn captured_pieces(&self, color: PieceColor) -> &[Piece] {
+        self.graveyard
+            .get(&color)
+            .map_or(&[], |grave| grave.as_slice())
+    }
+
+    /// How many of the given color's pieces of one type have been captured.
+    pub fn captured_count(&self, color: PieceColor, piece_type: PieceType) -> u8 {
+        self.captured_pieces(color)
+            .iter()
+            .filter(|piece| piece.piece_type == piece_type)
+            .count() as u8
+    }
+
+    /// Sums the value of the given color's pieces in centipawns.
+    pub fn material(&self, color: PieceColor) -> u32 {
+        self.pieces_of_color(color)
+            .map(|(_, piece)| piece.piece_type.value())
+            .sum()
+    }
+
+    /// Whether `color` has too little material left to ever checkmate: a lone king, or a
+    /// king with a single knight or bishop.
+    pub fn has_insufficient_material(&self, color: PieceColor) -> bool {
+        let others: Vec<PieceType> = self
+            .pieces_of_color(color)
+            .map(|(_, piece)| piece.piece_type)
+            .filter(|&piece_type| piece_type != PieceType::King)
+            .collect();
+        matches!(
+            others.as_slice(),
+            [] | [PieceType::Knight] | [PieceType::Bishop]
+        )
+    }
+
+    /// Whether `color` could checkmate by some series of legal moves, with the opponent's
+    /// help if need be. This is what decides whether a player whose flag falls loses.
+    ///
+    /// More than a single minor piece always could. A knight mates only with an opposing
+    /// piece hemming the king in, and a bishop only with an opposing piece that isn't a
+    /// bishop on its own square color.
+    pub fn could_checkmate(&self, color: PieceColor) -> bool {
+        if !self.has_insufficient_material(color) {
+            return true;
+        }
+        let minor_piece = self
+            .pieces_of_color(color)
+            .find(|(_, piece)| piece.piece_type != PieceType::King);
+        let mut blockers = self
+            .pieces_of_color(color.flip())
+            .filter(|(_, piece)| piece.piece_type != PieceType::King);
+
+        match minor_piece {
+            None => false,
+            Some((loc, piece)) if piece.piece_type == PieceType::Bishop => {
+                blockers.any(|(blocker_loc, blocker)| {
+                    blocker.piece_type != PieceType::Bishop
+                        || blocker_loc.is_light() != loc.is_light()
+                })
+            }
+            Some(_) => blockers.next().is_some(),
+        }
+    }
+
+    /// Whether neither side can ever checkmate, however badly the other plays, which
+    /// makes the game an immediate draw.
+    ///
+    /// For now this only recognizes too little material on the board: king against king,
+    /// king and a minor piece against a lone king, and kings with one bishop each where both
+    /// bishops travel on the same color. Blocked pawn structures aren't detected yet.
+    pub fn is_dead_position(&self) -> bool {
+        let minor_pieces = |color: PieceColor| -> Option<Vec<(PieceLoc, PieceType)>> {
+            let others: Vec<(PieceLoc, PieceType)> = self
+                .pieces_of_color(color)
+                .map(|(loc, piece)| (loc, piece.piece_type))
+                .filter(|&(_, piece_type)| piece_type != PieceType::King)
+                .collect();
+            self.has_insufficient_material(color).then_some(others)
+        };
+
+        match (
+            minor_pieces(PieceColor::White),
+            minor_pieces(PieceColor::Black),
+        ) {
+            (Some(white), Some(black)) => match (white.as_slice(), black.as_slice()) {
+                ([], _) | (_, []) => true,
+                ([(white_loc, PieceType::Bishop)], [(black_loc, PieceType::Bishop)]) => {
+                    white_loc.is_light() == black_loc.is_light()
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// White's material minus black's, in centipawns.
+    pub fn material_balance(&self) -> i32 {
+        self.material(PieceColor::White) as i32 - self.material(PieceColor::Black) as i32
+    }
+
+    /// Iterates over every square of the board with whatever stands on it, so a frontend
+    /// can draw the board without parsing `Display`.
+    ///
+    /// The squares come rank by rank from white's side, each rank from the a-file onwards:
+    /// a1, b1, ..., h1, a2, ..., h8 on a standard board.
+    pub fn squares(&self) -> impl Iterator<Item = (PieceLoc, Option<Piece>)> + '_ {
+        let files = usize::from(self.files);
+        self.board.iter().enumerate().map(move |(idx, square)| {
+            (
+                PieceLoc::new((idx / files) as u8, (idx % files) as u8),
+                *square,
+            )
+        })
+    }
+
+    /// Iterates over every piece of the given color, alongside the square it stands on.
+    pub fn pieces_of_color(
+        &self,
+        color: PieceColor,
+    ) -> impl Iterator<Item = (PieceLoc, Piece)> + '_ {
+        let files = usize::from(self.files);
+        self.board
+            .iter()
+            .enumerate()
+            .filter_map(move |(idx, square)| match square {
+                Some(piece) if piece.color == color => Some((
+                    PieceLoc::new((idx / files) as u8, (idx % files) as u8),
+                    *piece,
+                )),
+                _ => None,
+            })
+    }
+
+    // Squares are stored rank by rank, so consecutive ranks are `files` squares apart
+    fn get_board_index_from_loc(&self, loc: PieceLoc) -> usize {
+        usize::from(loc.rank) * usize::from(self.files) + usize::from(loc.file)
+    }
+}
+
+// Boards are equal when they hold the same position, regardless of move history
+impl PartialEq for Board {
+    fn eq(&self, other: &Board) -> bool {
+        self.same_position(other)
+    }
+}
+
+impl Eq for Board {}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_oriented(PieceColor::White))
+    }
+}
+
+pub mod board_display {
+    // List of all valid alpha representation of ranks
+    static ALPHA_RANKS_UPPER: [char; 26] = [
+        'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R',
+        'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+    ];
+
+    use super::Board;
+    use crate::game::piece::{
+        piece_info::{PieceColor, PieceLoc, PieceType},
+        Piece,
+    };
+
+    pub fn get_graveyard_display(board: &Board) -> String {
+        let mut output: String = String::from("Graveyard:");
+        let mut piece_display: [String; 2] = [
+            String::from("\n\tWhite pieces:"),
+            String::from("\n\tBlack pieces:"),
+        ];
+        for color in [PieceColor::White, PieceColor::Black] {
+            let display_index = match color {
+                PieceColor::White => 0,
+                PieceColor::Black => 1,
+            };
+            let mut found_captured_of_color = false;
+            for p_type in [
+                PieceType::Pawn,
+                PieceType::Knight,
+                PieceType::Bishop,
+                PieceType::Rook,
+                PieceType::Queen,
+                PieceType::King,
+            ] {
+                let captured = board.captured_count(color, p_type);
+                if captured > 0 {
+                    piece_display[display_index]
+                        .push_str(format!("\n\t\t{}x {}", captured, p_type).as_str());
+                    found_captured_of_color = true;
+                }
+            }
+            if !found_captured_of_color {
+                piece_display[display_index].push_str("\n\t\tNo pieces have been captured yet.");
+            }
+        }
+        piece_display.iter().for_each(|color| {
+            output.push_str(color.as_str());
+        });
+        output
+    }
+
+    pub fn get_movelist_display(board: &Board) -> String {
+        let mut output: String = String::from("Moves:");
+
+        // Each numbered move starts a new line, so black's reply shares white's line
+        for (label, san) in board.numbered_san_history() {
+            if let Some(label) = label {
+                output.push('\n');
+                output.push_str(format!("{} ", label).as_str());
+            }
+            output.push_str(format!("{} ", san).as_str());
+        }
+        output
+    }
+
+    // Shows how close the game is to a fifty-move draw, counted in halfmoves since the last
+    // pawn move or capture
+    pub fn get_halfmove_clock_display(board: &Board) -> String {
+        format!(
+            "Halfmove clock: {}/100 toward fifty-move draw",
+            board.halfmove_clock()
+        )
+    }
+
+    // Draws the squares with empty squares as '.', rank numbers down the left and file
+    // letters along the bottom. Black's perspective turns the board around, flipping both
+    // the ranks and the files.
+    pub fn get_grid_display(
+        board: &Board,
+        perspective: PieceColor,
+        piece_char: impl Fn(&Piece) -> char,
+    ) -> String {
+        get_highlighted_grid_display(board, perspective, piece_char, &[])
+    }
+
+    // Draws the grid as `get_grid_display` does, replacing each highlighted square with '*'
+    // when it is empty and 'x' when there is a piece on it to capture
+    pub fn get_highlighted_grid_display(
+        board: &Board,
+        perspective: PieceColor,
+        piece_char: impl Fn(&Piece) -> char,
+        highlighted: &[PieceLoc],
+    ) -> String {
+        let (ranks, files): (Vec<u8>, Vec<u8>) = match perspective {
+            PieceColor::White => ((0..board.ranks).rev().collect(), (0..board.files).collect()),
+            PieceColor::Black => ((0..board.ranks).collect(), (0..board.files).rev().collect()),
+        };
+
+        let mut output: String = "".to_string();
+        for &rank in &ranks {
+            output.push_str(format!("{} ", rank + 1).as_str());
+            for &file in &files {
+                let loc = PieceLoc::new(rank, file);
+                let display_char = match board.get_piece_at_location(loc) {
+                    Some(_) if highlighted.contains(&loc) => 'x',
+                    None if highlighted.contains(&loc) => '*',
+                    Some(piece) => piece_char(&piece),
+                    None => '.',
+                };
+
+                output.push(display_char);
+                output.push(' ');
+            }
+            output.push('\n')
+        }
+
+        output.push_str(&get_file_labels_display(&files));
+        output
+    }
+
+    // The file letters beneath the board, lined up with the two-character square cells
+    fn get_file_labels_display(files: &[u8]) -> String {
+        let mut output = String::from(" ");
+        for &file in files {
+            output.push(' ');
+            output.push_str(&file_name(file));
+        }
+        output.push('\n');
+        output
+    }
+
+    // Draws the board from white's side with each square boxed in, the pieces cased as in
+    // FEN and empty squares left blank
+    pub fn get_diagram_display(board: &Board) -> String {
+        // Rank numbers can run to two digits, so the left margin fits the widest
+        let margin = board.ranks.to_string().len();
+        let separator = format!(
+            "{} +{}\n",
+            " ".repeat(margin),
+            "---+".repeat(usize::from(board.files))
+        );
+
+        let mut output = separator.clone();
+        for rank in (0..board.ranks).rev() {
+            output.push_str(format!("{:>margin$} |", rank + 1).as_str());
+            for file in 0..board.files {
+                let display_char = board
+                    .get_piece_at_location(PieceLoc::new(rank, file))
+                    .map_or(' ', |piece| get_piece_char_cased(&piece));
+                output.push_str(format!(" {} |", display_char).as_str());
+            }
+            output.push('\n');
+            output.push_str(&separator);
+        }
+
+        // Each file letter sits in the middle of its column
+        let mut labels = " ".repeat(margin + 1);
+        for file in 0..board.files {
+            labels.push_str(format!("  {} ", file_name(file)).as_str());
+        }
+        output.push_str(labels.trim_end());
+        output.push('\n');
+        output
+    }
+
+    const ANSI_RESET: &str = "\x1b[0m";
+    const ANSI_LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+    const ANSI_DARK_SQUARE: &str = "\x1b[48;5;94m";
+    const ANSI_WHITE_PIECE: &str = "\x1b[1;97m";
+    const ANSI_BLACK_PIECE: &str = "\x1b[1;30m";
+
+    pub fn square_is_light(loc: PieceLoc) -> bool {
+        loc.is_light()
+    }
+
+    // Draws the board from white's side with each square as a shaded, two-character cell
+    pub fn get_ansi_grid_display(board: &Board) -> String {
+        let mut output: String = "".to_string();
+        for rank in (0..board.ranks).rev() {
+            output.push_str(format!("{} ", rank + 1).as_str());
+            for file in 0..board.files {
+                let loc = PieceLoc::new(rank, file);
+                let background = if square_is_light(loc) {
+                    ANSI_LIGHT_SQUARE
+                } else {
+                    ANSI_DARK_SQUARE
+                };
+                let cell = match board.get_piece_at_location(loc) {
+                    Some(piece) => {
+                        let foreground = match piece.color {
+                            PieceColor::White => ANSI_WHITE_PIECE,
+                            PieceColor::Black => ANSI_BLACK_PIECE,
+                        };
+                        format!("{}{}", foreground, get_piece_display(&piece, false))
+                    }
+                    None => String::from(" "),
+                };
+
+                output.push_str(format!("{}{} {}", background, cell, ANSI_RESET).as_str());
+            }
+            output.push('\n')
+        }
+
+        let files: Vec<u8> = (0..board.files).collect();
+        output.push_str(&get_file_labels_display(&files));
+        output
+    }
+
+    // White pieces are drawn in uppercase and black pieces in lowercase, as in FEN
+    pub fn get_piece_char_cased(piece: &Piece) -> char {
+        let piece_char = get_piece_display(piece, false);
+        match piece.color {
+            PieceColor::White => piece_char,
+            PieceColor::Black => piece_char.to_ascii_lowercase(),
+        }
+    }
+
+    pub fn get_piece_unicode(piece: &Piece) -> char {
+        match (piece.color, piece.piece_type) {
+            (PieceColor::White, PieceType::King) => '\u{2654}',
+            (PieceColor::White, PieceType::Queen) => '\u{2655}',
+            (PieceColor::White, PieceType::Rook) => '\u{2656}',
+            (PieceColor::White, PieceType::Bishop) => '\u{2657}',
+            (PieceColor::White, PieceType::Knight) => '\u{2658}',
+            (PieceColor::White, PieceType::Pawn) => '\u{2659}',
+            (PieceColor::Black, PieceType::King) => '\u{265A}',
+            (PieceColor::Black, PieceType::Queen) => '\u{265B}',
+            (PieceColor::Black, PieceType::Rook) => '\u{265C}',
+            (PieceColor::Black, PieceType::Bishop) => '\u{265D}',
+            (PieceColor::Black, PieceType::Knight) => '\u{265E}',
+            (PieceColor::Black, PieceType::Pawn) => '\u{265F}',
+        }
+    }
+
+    pub fn get_piece_display(piece: &Piece, pawn_blank: bool) -> char {
+        match piece.piece_type {
+            PieceType::Pawn if pawn_blank => ' ',
+            piece_type => piece_type.to_char(),
+        }
+    }
+
+    pub fn convert_rank_alpha_to_numeric(rank: char) -> Option<u8> {
+        let rank = rank.clone().to_ascii_uppercase();
+
+        if ALPHA_RANKS_UPPER.contains(&rank) {
+            match ALPHA_RANKS_UPPER.iter().position(|c| c == &rank) {
+                Some(i) => return Some(i as u8),
+                None => return None,
+            };
+        }
+        None
+    }
+
+    pub fn convert_rank_numeric_to_alpha(rank: u8) -> Option<char> {
+        let rank = usize::from(rank);
+        if rank < ALPHA_RANKS_UPPER.len() {
+            ALPHA_RANKS_UPPER.get(rank).copied()
+        } else {
+            None
+        }
+    }
+
+    // Names a file in lowercase letters the way spreadsheet columns are named, so the
+    // files after 'z' are "aa", "ab" and so on
+    pub fn file_name(file: u8) -> String {
+        let mut name = Vec::new();
+        let mut remaining = usize::from(file) + 1;
+        while remaining > 0 {
+            remaining -= 1;
+            name.push(ALPHA_RANKS_UPPER[remaining % 26].to_ascii_lowercase());
+            remaining /= 26;
+        }
+        name.iter().rev().collect()
+    }
+
+    // Reads a file named by `file_name`, in either case
+    pub fn file_from_name(name: &str) -> Option<u8> {
+        if name.is_empty() {
+            return None;
+        }
+        let mut file: usize = 0;
+        for c in name.chars() {
+            let letter = usize::from(convert_rank_alpha_to_numeric(c)?);
+            file = file.checked_mul(26)?.checked_add(letter + 1)?;
+        }
+        u8::try_from(file - 1).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stalemate_risk_king_and_queen_against_cornered_king() {
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(4, 6),
+                Piece::new(PieceType::Queen, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::King, PieceColor::Black),
+            );
+
+        // The black king's only escape is H7
+        let black_to_move = Board {
+            current_turn: PieceColor::Black,
+            ..board.clone()
+        };
+        assert_eq!(1, black_to_move.legal_move_count());
+
+        assert!(board.stalemate_risk());
+    }
+
+    #[test]
+    fn test_squares_run_from_a1_to_h8() {
+        let board = Board::new();
+        let squares: Vec<(PieceLoc, Option<Piece>)> = board.squares().collect();
+
+        assert_eq!(64, squares.len());
+        let names: Vec<String> = squares.iter().map(|(loc, _)| loc.to_string()).collect();
+        assert_eq!(["a1", "b1"], names[..2]);
+        assert_eq!(["h1", "a2"], names[7..9]);
+        assert_eq!("h8", names[63]);
+
+        assert_eq!(
+            Some(Piece::new(PieceType::Rook, PieceColor::White)),
+            squares[0].1
+        );
+        assert_eq!(None, squares[27].1);
+        assert_eq!(
+            Some(PieceColor::Black),
+            squares[63].1.map(|piece| piece.color)
+        );
+    }
+
+    #[test]
+    fn test_legal_moves_for() {
+        let board = Board::new();
+        let knight_moves: Vec<String> = board
+            .legal_moves_for(PieceLoc::from_notation("g1").unwrap())
+            .iter()
+            .map(|m| m.end_pos.to_string())
+            .collect();
+
+        assert_eq!(vec!["f3", "h3"], knight_moves);
+        // Boxed in, empty and the wrong color
+        assert!(board
+            .legal_moves_for(PieceLoc::from_notation("a1").unwrap())
+            .is_empty());
+        assert!(board
+            .legal_moves_for(PieceLoc::from_notation("e4").unwrap())
+            .is_empty());
+        assert!(board
+            .legal_moves_for(PieceLoc::from_notation("e7").unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_highlighted_display_marks_targets_and_captures() {
+        let board = Board::from_fen("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+        let queen = PieceLoc::from_notation("d1").unwrap();
+        let targets: Vec<PieceLoc> = board
+            .legal_moves_for(queen)
+            .iter()
+            .map(|m| m.end_pos)
+            .collect();
+
+        let display = board.to_string_highlighted(PieceColor::White, &targets);
+        let rows: Vec<&str> = display.lines().collect();
+        assert_eq!("5 . . . x . . . * ", rows[3]);
+        assert_eq!("4 * . . * . . * . ", rows[4]);
+        assert_eq!("2 . . * * * . . . ", rows[6]);
+        assert_eq!("1 * * * Q K . . . ", rows[7]);
+    }
+
+    #[test]
+    fn test_transpositions_are_the_same_position() {
+        let kingside_first = pgn_board("1. Nf3 Nf6 2. Nc3 Nc6");
+        let queenside_first = pgn_board("1. Nc3 Nc6 2. Nf3 Nf6");
+
+        assert!(kingside_first.same_position(&queenside_first));
+        assert_eq!(kingside_first, queenside_first);
+        assert_ne!(kingside_first.move_list, queenside_first.move_list);
+    }
+
+    #[test]
+    fn test_knights_returning_home_repeat_the_start_position() {
+        let board = pgn_board("1. Nf3 Nf6 2. Ng1 Ng8");
+
+        assert_eq!(Board::new(), board);
+        assert_ne!(Board::new(), pgn_board("1. Nf3 Nf6 2. Ng1"));
+    }
+
+    #[test]
+    fn test_en_passant_and_castling_rights_distinguish_positions() {
+        // The same pieces, but only the double push leaves an en passant target
+        assert_ne!(pgn_board("1. e4 e5"), pgn_board("1. e3 e6 2. e4 e5"));
+        // The same pieces, but the rook's trip cost white the kingside right
+        assert_ne!(
+            pgn_board("1. Nf3 Nf6 2. Nc3 Nc6"),
+            pgn_board("1. Nf3 Nf6 2. Rg1 Nc6 3. Rh1 Ng8 4. Nc3 Nf6")
+        );
+    }
+
+    fn pgn_board(pgn: &str) -> Board {
+        Board::from_pgn(pgn).unwrap()
+    }
+
+    #[test]
+    fn test_move_check_kind() {
+        let board = pgn_board("1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6");
+        let san_move = |san| Move::from_san(&board, san).unwrap();
+
+        assert_eq!(
+            Some(CheckKind::Checkmate),
+            board.move_check_kind(&san_move("Qxf7"))
+        );
+        assert_eq!(
+            Some(CheckKind::Check),
+            board.move_check_kind(&san_move("Bxf7"))
+        );
+        assert_eq!(None, board.move_check_kind(&san_move("Qh4")));
+        assert!(board.move_gives_check(&san_move("Bxf7")));
+        assert!(!board.move_gives_check(&san_move("d3")));
+    }
+
+    #[test]
+    fn test_attackers_of() {
+        // The black pawn on d5 is attacked by the knight, the e4 pawn and the front rook,
+        // with the back rook only x-raying it. The queen and c6 pawn defend it.
+        let board = Board::from_fen("3qk3/8/2p5/3p4/1N1RP3/8/8/3RK3 w - - 0 1").unwrap();
+        let d5 = PieceLoc::from_notation("d5").unwrap();
+        let squares = |locs: Vec<PieceLoc>| -> Vec<String> {
+            locs.iter().map(|loc| loc.to_string()).collect()
+        };
+
+        assert_eq!(
+            vec!["b4", "d4", "e4"],
+            squares(board.attackers_of(d5, PieceColor::White))
+        );
+        assert_eq!(
+            vec!["c6", "d8"],
+            squares(board.attackers_of(d5, PieceColor::Black))
+        );
+        assert!(board
+            .attackers_of(PieceLoc::from_notation("a8").unwrap(), PieceColor::White)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_mobility_of_either_side() {
+        let board = pgn_board("1. e4");
+
+        assert_eq!(30, board.mobility(PieceColor::White));
+        assert_eq!(20, board.mobility(PieceColor::Black));
+        assert_eq!(PieceColor::Black, board.current_turn);
+
+        // A cornered king with a single square to go to
+        let board = Board::from_fen("k7/8/2K5/1Q6/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(1, board.mobility(PieceColor::Black));
+    }
+
+    #[test]
+    fn test_with_size_sets_up_smaller_boards() {
+        assert_eq!(Board::new().to_fen(), Board::with_size(8, 8).to_fen());
+        assert!(Board::with_size(8, 8)
+            .castling_rook_file(PieceColor::White, true)
+            .is_some());
+
+        let board = Board::with_size(6, 6);
+        assert_eq!("rnqknr/pppppp/6/6/PPPPPP/RNQKNR w - - 0 1", board.to_fen());
+        assert_eq!(None, board.castling_rook_file(PieceColor::White, true));
+    }
+
+    #[test]
+    fn test_playing_on_a_six_by_six_board() {
+        let san_moves = ["a4", "f4", "Ra3", "Nd4", "Rd3", "Nxc2"];
+        let mut board = Board::with_size(6, 6);
+        assert_eq!(6 + 6 + 2 + 2, board.legal_move_count());
+
+        for san in san_moves {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        assert_eq!(
+            "rnqk1r/ppppp1/P4p/3R2/1PnPPP/1NQKNR w - - 0 4",
+            board.to_fen()
+        );
+        assert!(board.perft(2) > 0);
+    }
+
+    #[test]
+    fn test_en_passant_on_a_six_by_six_board() {
+        // Black's pawn skips over c4, where the white pawn on b3 can take it
+        let mut board = Board::with_size(6, 6);
+        for san in ["b3", "c3", "bxc4"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert_eq!(
+            "rnqknr/pp1ppp/2P3/6/P1PPPP/RNQKNR b - - 0 2",
+            board.to_fen()
+        );
+    }
+
+    #[test]
+    fn test_dead_positions() {
+        for (fen, dead) in [
+            ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", true),
+            ("8/8/4k3/8/8/3K4/8/6N1 w - - 0 1", true),
+            ("8/8/4k3/8/8/3K4/8/5B2 w - - 0 1", true),
+            // Bishops on the same color can never cover the king's escape squares
+            ("2b5/8/4k3/8/8/3K4/8/5B2 w - - 0 1", true),
+            // On opposite colors a mate is possible if black cooperates
+            ("1b6/8/4k3/8/8/3K4/8/5B2 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/8/5N1n w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/4P3/8 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/8/5R2 w - - 0 1", false),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(dead, board.is_dead_position(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_could_checkmate() {
+        for (fen, white_could_mate) in [
+            ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/8/6N1 w - - 0 1", false),
+            // Any black piece can stand in its own king's way
+            ("8/4p3/4k3/8/8/3K4/8/6N1 w - - 0 1", true),
+            ("8/8/4k3/8/8/3K4/8/5B2 w - - 0 1", false),
+            ("2b5/8/4k3/8/8/3K4/8/5B2 w - - 0 1", false),
+            ("1b6/8/4k3/8/8/3K4/8/5B2 w - - 0 1", true),
+            ("8/4n3/4k3/8/8/3K4/8/5B2 w - - 0 1", true),
+            ("8/8/4k3/8/8/3K4/4P3/8 w - - 0 1", true),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(
+                white_could_mate,
+                board.could_checkmate(PieceColor::White),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_capturing_down_to_bare_kings_is_a_draw() {
+        let board = Board::from_fen("8/8/8/4p3/3K4/8/8/7k w - - 0 1").unwrap();
+        let capture = Move::from_san(&board, "Kxe5").unwrap();
+        let board = board.move_piece(capture).unwrap();
+
+        assert_eq!(
+            GameStatus::draw(DrawReason::InsufficientMaterial),
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        // The e4 knight is pinned by the rook and the c3 pawn by the bishop, while the g3
+        // bishop and f2 pawn shield the king from the queen together
+        let board = Board::from_fen("4r1k1/8/8/b7/4N2q/2P3B1/5P2/4K3 w - - 0 1").unwrap();
+        let mut pins = board.pinned_pieces(PieceColor::White);
+        pins.sort_by_key(|(pinned, _)| (pinned.rank, pinned.file));
+
+        assert_eq!(
+            vec![
+                (
+                    PieceLoc::from_notation("c3").unwrap(),
+                    PieceLoc::from_notation("a5").unwrap()
+                ),
+                (
+                    PieceLoc::from_notation("e4").unwrap(),
+                    PieceLoc::from_notation("e8").unwrap()
+                ),
+            ],
+            pins
+        );
+        assert!(board.pinned_pieces(PieceColor::Black).is_empty());
+    }
+
+    #[test]
+    fn test_two_pieces_on_a_line_are_not_pinned() {
+        let board = Board::from_fen("4r1k1/8/8/8/4N3/4P3/8/4K3 w - - 0 1").unwrap();
+
+        assert!(board.pinned_pieces(PieceColor::White).is_empty());
+    }
+
+    fn setup_castling(color: PieceColor) -> Board {
+        let mut board = Board::new();
+        let back_rank = match color {
+            PieceColor::White => 0,
+            PieceColor::Black => 56,
+        };
+        for file in [1, 2, 3, 5, 6] {
+            board.board[back_rank + file] = None;
+        }
+        board.current_turn = color;
+        board
+    }
+
+    fn castle(board: Board, rank: u8, dest_file: u8) -> Board {
+        let king_loc = PieceLoc::new(rank, 4);
+        let king = board.get_piece_at_location(king_loc).unwrap();
+        let castle = Move::new(&board, &king, &king_loc, &PieceLoc::new(rank, dest_file)).unwrap();
+        assert_eq!(MoveType::Castling, castle.move_type);
+        board.move_piece(castle).unwrap()
+    }
+
+    #[test]
+    fn test_castle_kingside_moves_rook() {
+        let board = castle(setup_castling(PieceColor::White), 0, 6);
+
+        assert_eq!(PieceType::King, board.board[6].unwrap().piece_type);
+        let rook = board.board[5].unwrap();
+        assert_eq!(PieceType::Rook, rook.piece_type);
+        assert!(rook.has_moved);
+        assert!(board.board[4].is_none());
+        assert!(board.board[7].is_none());
+    }
+
+    #[test]
+    fn test_castle_queenside_moves_rook() {
+        let board = castle(setup_castling(PieceColor::White), 0, 2);
+
+        assert_eq!(PieceType::King, board.board[2].unwrap().piece_type);
+        let rook = board.board[3].unwrap();
+        assert_eq!(PieceType::Rook, rook.piece_type);
+        assert!(rook.has_moved);
+        assert!(board.board[0].is_none());
+        assert!(board.board[4].is_none());
+    }
+
+    #[test]
+    fn test_black_castle_kingside_moves_rook() {
+        let board = castle(setup_castling(PieceColor::Black), 7, 6);
+
+        assert_eq!(PieceType::King, board.board[62].unwrap().piece_type);
+        let rook = board.board[61].unwrap();
+        assert_eq!(PieceType::Rook, rook.piece_type);
+        assert_eq!(PieceColor::Black, rook.color);
+        assert!(board.board[63].is_none());
+    }
+
+    #[test]
+    fn test_restore_snapshot_after_several_moves() {
+        let mut board = Board::new();
+        for san in ["e4", "d5"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        let expected = board.clone();
+        let snap = board.snapshot();
+
+        for san in ["exd5", "Qxd5", "Nc3"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        assert_eq!(1, board.captured_count(PieceColor::White, PieceType::Pawn));
+
+        board.restore(snap);
+        assert_eq!(expected.board, board.board);
+        assert_eq!(expected.move_list, board.move_list);
+        assert_eq!(expected.graveyard, board.graveyard);
+        assert_eq!(expected.current_turn, board.current_turn);
+    }
+
+    #[test]
+    fn test_game_status_ongoing_at_start() {
+        assert_eq!(GameStatus::Ongoing, Board::new().game_status());
+    }
+
+    #[test]
+    fn test_game_status_fools_mate() {
+        let mut board = Board::new();
+        for san in ["f3", "e5", "g4", "Qh4"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert_eq!(
+            GameStatus::Checkmate {
+                winner: PieceColor::Black
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_fifty_knight_shuffles_draw() {
+        let mut board = Board::new();
+        for _ in 0..25 {
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                assert!(!board.is_fifty_move_draw());
+                let m = Move::from_san(&board, san).unwrap();
+                board = board.move_piece(m).unwrap();
+            }
+        }
+
+        assert_eq!(100, board.halfmove_clock);
+        assert!(board.is_fifty_move_draw());
+        // Shuffling repeated the position long ago, which ends the game without a claim
+        assert_eq!(
+            GameStatus::draw(DrawReason::FivefoldRepetition),
+            board.game_status()
+        );
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert_eq!(
+            GameStatus::Draw {
+                reason: DrawReason::FiftyMoveRule,
+                claimable: true
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_pawn_move_resets_halfmove_clock() {
+        let mut board = Board::new();
+        for san in ["Nf3", "Nf6"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        assert_eq!(2, board.halfmove_clock);
+
+        let m = Move::from_san(&board, "e4").unwrap();
+        board = board.move_piece(m).unwrap();
+        assert_eq!(0, board.halfmove_clock);
+    }
+
+    #[test]
+    fn test_threefold_repetition_can_be_claimed() {
+        let mut board = Board::new();
+        for _ in 0..2 {
+            assert_eq!(GameStatus::Ongoing, board.game_status());
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                let m = Move::from_san(&board, san).unwrap();
+                board = board.move_piece(m).unwrap();
+            }
+        }
+
+        assert!(board.is_threefold_repetition());
+        assert_eq!(
+            GameStatus::Draw {
+                reason: DrawReason::ThreefoldRepetition,
+                claimable: true
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_fivefold_repetition_draws() {
+        let mut board = Board::new();
+        for shuffles in 1..=4 {
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                let m = Move::from_san(&board, san).unwrap();
+                board = board.move_piece(m).unwrap();
+            }
+            // The starting position, then once more after every shuffle
+            assert_eq!(shuffles + 1, board.repetition_count());
+        }
+
+        assert!(board.is_fivefold_repetition());
+        assert!(!board.is_fifty_move_draw());
+        assert_eq!(
+            GameStatus::Draw {
+                reason: DrawReason::FivefoldRepetition,
+                claimable: false
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_repetition_count_follows_unmake_and_irreversible_moves() {
+        let mut board = Board::new();
+        let mut undos = Vec::new();
+        for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            let m = Move::from_san(&board, san).unwrap();
+            undos.push(board.make_move(m));
+        }
+        assert_eq!(2, board.repetition_count());
+
+        board.unmake_move(undos.pop().unwrap());
+        assert_eq!(1, board.repetition_count());
+
+        // Once a pawn has moved, the positions before it can't be reached again
+        let mut board = pgn_board("1. Nf3 Nf6 2. Ng1 Ng8 3. e3 e6 4. Nf3 Nf6 5. Ng1 Ng8");
+        assert_eq!(2, board.repetition_count());
+        let m = Move::from_san(&board, "Nf3").unwrap();
+        board = board.move_piece(m).unwrap();
+        assert_eq!(2, board.repetition_count());
+    }
+
+    #[test]
+    fn test_seventy_five_move_draw() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 120").unwrap();
+        assert!(!board.is_seventy_five_move_draw());
+
+        let m = Move::from_san(&board, "Ra2").unwrap();
+        board = board.move_piece(m).unwrap();
+        assert_eq!(150, board.halfmove_clock());
+        assert!(board.is_seventy_five_move_draw());
+        assert_eq!(
+            GameStatus::Draw {
+                reason: DrawReason::SeventyFiveMoveRule,
+                claimable: false
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_game_status_stalemate() {
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 2),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(5, 1),
+                Piece::new(PieceType::Queen, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(7, 0),
+                Piece::new(PieceType::King, PieceColor::Black),
+            )
+            .with_turn(PieceColor::Black);
+
+        assert_eq!(
+            GameStatus::Draw {
+                reason: DrawReason::Stalemate,
+                claimable: false
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_board_index_strides_by_files() {
+        let mut board = Board::new();
+        board.ranks = 8;
+        board.files = 10;
+        board.board = vec![None; 80];
+
+        let loc = PieceLoc::new(1, 9);
+        assert_eq!(19, board.get_board_index_from_loc(loc));
+
+        board.board[19] = Some(Piece::new(PieceType::Rook, PieceColor::White));
+        assert_eq!(
+            PieceType::Rook,
+            board.get_piece_at_location(loc).unwrap().piece_type
+        );
+        assert!(board.get_piece_at_location(PieceLoc::new(2, 1)).is_none());
+    }
+
+    #[test]
+    fn test_no_stalemate_risk_at_start() {
+        assert!(!Board::new().stalemate_risk());
+    }
+
+    #[test]
+    fn test_pieces_of_color() {
+        let board = Board::new();
+        let white: Vec<(PieceLoc, Piece)> = board.pieces_of_color(PieceColor::White).collect();
+
+        assert_eq!(16, white.len());
+        assert!(white
+            .iter()
+            .all(|(_, piece)| piece.color == PieceColor::White));
+        assert_eq!(
+            (
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::Rook, PieceColor::White)
+            ),
+            white[0]
+        );
+
+        let (loc, piece) = board.pieces_of_color(PieceColor::Black).last().unwrap();
+        assert_eq!(PieceLoc::new(7, 7), loc);
+        assert_eq!(PieceType::Rook, piece.piece_type);
+    }
+
+    #[test]
+    fn test_material_balance() {
+        let mut board = Board::new();
+        assert_eq!(0, board.material_balance());
+        assert_eq!(
+            8 * 100 + 2 * 320 + 2 * 330 + 2 * 500 + 900,
+            board.material(PieceColor::White)
+        );
+
+        // Remove black's queen
+        board.board[59] = None;
+        assert_eq!(900, board.material_balance());
+        assert_eq!(3100, board.material(PieceColor::Black));
+    }
+
+    #[test]
+    fn test_find_king() {
+        let mut board = Board::new();
+        assert_eq!(
+            Some(PieceLoc::new(0, 4)),
+            board.find_king(PieceColor::White)
+        );
+        assert_eq!(
+            Some(PieceLoc::new(7, 4)),
+            board.find_king(PieceColor::Black)
+        );
+
+        board.board[60] = None;
+        assert_eq!(None, board.find_king(PieceColor::Black));
+        assert!(!board.is_in_check(PieceColor::Black));
+    }
+
+    #[test]
+    fn test_is_square_attacked_from_start() {
+        let board = Board::new();
+
+        // Pawns attack diagonally but not the squares they push to
+        assert!(board.is_square_attacked(PieceLoc::new(2, 3), PieceColor::White));
+        assert!(board.is_square_attacked(PieceLoc::new(5, 4), PieceColor::Black));
+        // The knights reach over the pawns, but the rooks are blocked by them
+        assert!(board.is_square_attacked(PieceLoc::new(2, 0), PieceColor::White));
+        assert!(!board.is_square_attacked(PieceLoc::new(3, 0), PieceColor::White));
+        // Kings attack their own pieces' squares as well
+        assert!(board.is_square_attacked(PieceLoc::new(1, 4), PieceColor::White));
+        assert!(!board.is_square_attacked(PieceLoc::new(3, 4), PieceColor::Black));
+    }
+
+    #[test]
+    fn test_move_piece_rejects_moves_from_another_position() {
+        let board = Board::new();
+        let e4 = Move::from_san(&board, "e4").unwrap();
+        let board = board.move_piece(e4.clone()).unwrap();
+
+        // Replaying the same move is now out of turn, and the pawn has left e2
+        assert_eq!(
+            MoveError::PieceNotAtStart {
+                start: PieceLoc::new(1, 4)
+            },
+            board.clone().move_piece(e4).unwrap_err()
+        );
+
+        let black_pawn = board.get_piece_at_location(PieceLoc::new(6, 4)).unwrap();
+        let illegal = Move {
+            piece: black_pawn,
+            start_pos: PieceLoc::new(6, 4),
+            end_pos: PieceLoc::new(3, 4),
+            move_type: MoveType::Normal,
+            capturing: false,
+            promotion: None,
+            captured: None,
+            captured_pos: None,
+        };
+        assert_eq!(
+            MoveError::RankDifferenceGreater,
+            board.move_piece(illegal).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_empty_graveyard_counts_every_piece_type() {
+        let board = Board::new();
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            assert!(board.captured_pieces(color).is_empty());
+            assert_eq!(0, board.captured_count(color, PieceType::King));
+        }
+    }
+
+    #[test]
+    fn test_graveyard_keeps_captures_in_order() {
+        let board = pgn_board("1. e4 d5 2. exd5 Qxd5 3. Nc3 Qxa2 4. Rxa2");
+        let captured_types = |color| -> Vec<PieceType> {
+            board
+                .captured_pieces(color)
+                .iter()
+                .map(|piece| piece.piece_type)
+                .collect()
+        };
+
+        assert_eq!(
+            vec![PieceType::Pawn, PieceType::Pawn],
+            captured_types(PieceColor::White)
+        );
+        assert_eq!(
+            vec![PieceType::Pawn, PieceType::Queen],
+            captured_types(PieceColor::Black)
+        );
+        assert_eq!(2, board.captured_count(PieceColor::White, PieceType::Pawn));
+        assert_eq!(
+            "Graveyard:\n\tWhite pieces:\n\t\t2x Pawn\n\tBlack pieces:\n\t\t1x Pawn\n\t\t1x Queen",
+            board_display::get_graveyard_display(&board)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_round_trip() {
+        let mut board = Board::new();
+        for san in ["e4", "d5", "exd5", "Qxd5", "Nc3"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        let json = serde_json::to_string(&board).unwrap();
+        // Enum keys in the graveyard come out as strings
+        assert!(json.contains(r#""graveyard":{"#));
+        assert!(json.contains(r#""White":[{"piece_type":"Pawn""#));
+
+        let restored: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(board.to_fen(), restored.to_fen());
+        assert_eq!(board.move_list, restored.move_list);
+        assert_eq!(board.graveyard, restored.graveyard);
+    }
+
+    #[test]
+    fn test_empty_board_builder() {
+        let knight = Piece::new(PieceType::Knight, PieceColor::Black);
+        let mut board = Board::empty(8, 8)
+            .with_piece(PieceLoc::new(3, 4), knight)
+            .with_turn(PieceColor::Black)
+            .with_clocks(12, 30);
+
+        assert_eq!(1, board.board.iter().flatten().count());
+        assert_eq!(
+            Some(knight),
+            board.get_piece_at_location(PieceLoc::new(3, 4))
+        );
+        assert_eq!(PieceColor::Black, board.current_turn);
+        assert_eq!(12, board.halfmove_clock);
+        assert_eq!(30, board.fullmove_number);
+
+        board.set_piece(PieceLoc::new(3, 4), None);
+        assert!(board.board.iter().all(|square| square.is_none()));
+        assert_eq!(80, Board::empty(8, 10).board.len());
+    }
+
+    #[test]
+    fn test_moving_king_or_rook_gives_up_castling_rights() {
+        let mut board = Board::new();
+        for san in ["h4", "e5", "Rh3", "Ke7"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert_eq!(None, board.castling_rook_file(PieceColor::White, true));
+        assert_eq!(Some(0), board.castling_rook_file(PieceColor::White, false));
+        assert_eq!(
+            CastlingRights::none(),
+            board.castling_rights[&PieceColor::Black]
+        );
+
+        // Returning the rook to its corner doesn't restore the right
+        let m = Move::from_san(&board, "Rh1").unwrap();
+        let board = board.move_piece(m).unwrap();
+        assert_eq!(None, board.castling_rook_file(PieceColor::White, true));
+    }
+
+    #[test]
+    fn test_captured_rook_gives_up_castling_right() {
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(0, 7),
+                Piece::new(PieceType::Rook, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(7, 4),
+                Piece::new(PieceType::King, PieceColor::Black),
+            )
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::Rook, PieceColor::Black),
+            )
+            .with_castling_rights(PieceColor::White, CastlingRights::standard(8))
+            .with_castling_rights(PieceColor::Black, CastlingRights::standard(8));
+
+        let m = Move::from_san(&board, "Rxh8+").unwrap();
+        let board = board.move_piece(m).unwrap();
+
+        assert_eq!(None, board.castling_rook_file(PieceColor::Black, true));
+        assert_eq!(None, board.castling_rook_file(PieceColor::White, true));
+    }
+
+    #[test]
+    fn test_castling_onto_the_rook_square_keeps_the_rook() {
+        // The kingside rook stands on g1, right where the king is headed
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(0, 6),
+                Piece::new(PieceType::Rook, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(7, 4),
+                Piece::new(PieceType::King, PieceColor::Black),
+            )
+            .with_castling_rights(
+                PieceColor::White,
+                CastlingRights {
+                    kingside: Some(6),
+                    queenside: None,
+                },
+            );
+
+        let castle = Move::from_san(&board, "O-O").unwrap();
+        assert!(!castle.capturing);
+        let mut board = board.move_piece(castle).unwrap();
+        assert_eq!("4k3/8/8/8/8/8/8/5RK1 b - - 1 1", board.to_fen());
+
+        let undo = board.make_move(Move::from_san(&board, "Kd7").unwrap());
+        board.unmake_move(undo);
+        assert_eq!("4k3/8/8/8/8/8/8/5RK1 b - - 1 1", board.to_fen());
+    }
+
+    #[test]
+    fn test_castling_onto_an_occupied_square_is_an_error() {
+        // The rook on f1 has a clear path, but a black knight sits on the king's square
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(0, 5),
+                Piece::new(PieceType::Rook, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(0, 6),
+                Piece::new(PieceType::Knight, PieceColor::Black),
+            )
+            .with_piece(
+                PieceLoc::new(7, 4),
+                Piece::new(PieceType::King, PieceColor::Black),
+            )
+            .with_castling_rights(
+                PieceColor::White,
+                CastlingRights {
+                    kingside: Some(5),
+                    queenside: None,
+                },
+            );
+        let king = board.get_piece_at_location(PieceLoc::new(0, 4)).unwrap();
+
+        assert_eq!(
+            MoveError::CannotCastleThroughPiece,
+            Move::new(&board, &king, &PieceLoc::new(0, 4), &PieceLoc::new(0, 6)).unwrap_err()
+        );
+        assert!(board
+            .legal_moves()
+            .iter()
+            .all(|m| m.move_type != MoveType::Castling));
+    }
+
+    #[test]
+    fn test_chess960_castling_lands_on_the_g_and_c_files() {
+        // The king on f1 steps one file to castle kingside, written as king takes rook
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R4K1R w KQ - 0 1").unwrap();
+
+        let castle = Move::from_san(&board, "O-O").unwrap();
+        assert_eq!(PieceLoc::new(0, 7), castle.end_pos);
+        let mut after = board.clone();
+        let undo = after.make_move(castle);
+        assert_eq!("4k3/8/8/8/8/8/8/R4RK1 b - - 1 1", after.to_fen());
+        after.unmake_move(undo);
+        assert_eq!(board.to_fen(), after.to_fen());
+
+        let castle = Move::from_san(&board, "O-O-O").unwrap();
+        assert_eq!(PieceLoc::new(0, 2), castle.end_pos);
+        let board = board.move_piece(castle).unwrap();
+        assert_eq!("4k3/8/8/8/8/8/8/2KR3R b - - 1 1", board.to_fen());
+    }
+
+    #[test]
+    fn test_chess960_castling_checks_every_square_the_king_crosses() {
+        // The king crosses c1 to g1, and the rook on e8 covers e1 but not the midpoint d1
+        let board = Board::from_fen("4r2k/8/8/8/8/8/8/1K4R1 w K - 0 1").unwrap();
+        let king = board.get_piece_at_location(PieceLoc::new(0, 1)).unwrap();
+
+        assert_eq!(
+            MoveError::CannotCastleThroughCheck,
+            Move::new(&board, &king, &PieceLoc::new(0, 1), &PieceLoc::new(0, 6)).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_unmake_move_restores_every_position() {
+        // Between them these cover captures, castling, en passant and promotions
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 3 7",
+            "rnbqkbnr/ppp2ppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+        ] {
+            let original = Board::from_fen(fen).unwrap();
+            let mut board = original.clone();
+
+            for m in original.legal_moves() {
+                let undo = board.make_move(m.clone());
+                assert_eq!(original.clone().apply_move(m).to_fen(), board.to_fen());
+                board.unmake_move(undo);
+
+                assert_eq!(original.board, board.board);
+                assert_eq!(original.graveyard, board.graveyard);
+                assert_eq!(original.castling_rights, board.castling_rights);
+                assert_eq!(original.to_fen(), board.to_fen());
+                assert!(board.move_list.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unmake_move_restores_has_moved_from_move() {
+        let mut board = Board::new();
+        let mut stale = Move::from_san(&board, "e4").unwrap();
+        // make_move records the piece as it stands on the board, not the caller's copy
+        stale.piece.has_moved = true;
+
+        let undo = board.make_move(stale);
+        assert!(!board.get_previous_move().unwrap().piece.has_moved);
+        assert!(
+            board
+                .get_piece_at_location(PieceLoc::new(3, 4))
+                .unwrap()
+                .has_moved
+        );
+        board.unmake_move(undo);
+
+        assert!(
+            !board
+                .get_piece_at_location(PieceLoc::new(1, 4))
+                .unwrap()
+                .has_moved
+        );
+        assert!(Move::from_san(&board, "e4").is_ok());
+    }
+
+    #[test]
+    fn test_side_to_move_and_move_counters() {
+        let board = Board::new();
+        assert_eq!(PieceColor::White, board.side_to_move());
+        assert_eq!(1, board.fullmove_number());
+        assert_eq!(0, board.halfmove_clock());
+
+        // The full move only advances after black's reply
+        let board = pgn_board("1. Nf3");
+        assert_eq!(PieceColor::Black, board.side_to_move());
+        assert_eq!(1, board.fullmove_number());
+        assert_eq!(1, board.halfmove_clock());
+
+        let board = pgn_board("1. Nf3 Nf6 2. e4");
+        assert_eq!(PieceColor::Black, board.side_to_move());
+        assert_eq!(2, board.fullmove_number());
+        assert_eq!(0, board.halfmove_clock());
+    }
+
+    #[test]
+    fn test_san_history_writes_moves_as_played() {
+        // Both the check and the knight's disambiguation depend on the position at the time
+        let board = pgn_board("1. e4 e5 2. Nf3 Nc6 3. Bb5 d6 4. Bxc6+ bxc6 5. d3 Nf6 6. Nbd2");
+
+        assert_eq!(
+            vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "d6", "Bxc6+", "bxc6", "d3", "Nf6", "Nbd2"],
+            board.san_history()
+        );
+    }
+
+    #[test]
+    fn test_san_history_from_fen_start() {
+        // Black moves first, and neither rook stands where the standard setup has one
+        let mut board = Board::from_fen("8/4k3/8/r7/8/8/8/R3K3 b - - 0 1").unwrap();
+        for san in ["Rxa1+", "Kd2", "Ra2+"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert_eq!(vec!["Rxa1+", "Kd2", "Ra2+"], board.san_history());
+    }
+
+    #[test]
+    fn test_preview_move_leaves_board_untouched() {
+        let board = Board::new();
+        let m = Move::from_san(&board, "e4").unwrap();
+
+        let preview = board.preview_move(&m).unwrap();
+        assert_eq!(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            preview.to_fen()
+        );
+        assert_eq!(Board::new().to_fen(), board.to_fen());
+        assert!(board.move_list.is_empty());
+
+        // A move for the other side is still rejected
+        let reply = Move::from_san(&preview, "e5").unwrap();
+        assert_eq!(
+            Err(MoveError::WrongColorPiece),
+            board.preview_move(&reply).map(|b| b.to_fen())
+        );
+    }
+
+    #[test]
+    fn test_checkers_in_double_check() {
+        // The rook checks down the e-file and the bishop along the diagonal
+        let board = Board::from_fen("4k3/8/8/1B6/8/8/8/4RK2 b - - 0 1").unwrap();
+        let mut checkers = board.checkers(PieceColor::Black);
+        checkers.sort_by_key(|loc| (loc.rank, loc.file));
+
+        assert_eq!(vec![PieceLoc::new(0, 4), PieceLoc::new(4, 1)], checkers);
+        assert!(board.checkers(PieceColor::White).is_empty());
+        assert!(Board::new().checkers(PieceColor::Black).is_empty());
+
+        // Only the king can answer a double check
+        let legal_moves = board.legal_moves();
+        assert!(!legal_moves.is_empty());
+        assert!(legal_moves
+            .iter()
+            .all(|m| m.piece.piece_type == PieceType::King));
+    }
+
+    #[test]
+    fn test_defaults_start_a_new_game() {
+        #[derive(Default)]
+        struct Session {
+            board: Board,
+            status: GameStatus,
+            to_move: PieceColor,
+        }
+        let session = Session::default();
+
+        assert_eq!(Board::new().to_fen(), session.board.to_fen());
+        assert_eq!(GameStatus::Ongoing, session.status);
+        assert_eq!(PieceColor::White, session.to_move);
+        assert_eq!(MoveType::Normal, MoveType::default());
+    }
+
+    #[test]
+    fn test_position_key_equal_for_transposed_games() {
+        let a = pgn_board("1. Nf3 Nf6 2. Nc3 Nc6");
+        let b = pgn_board("1. Nc3 Nc6 2. Nf3 Nf6");
+        let c = pgn_board("1. Nc3 Nf6 2. Nf3 Nc6 3. Ng1");
+
+        assert_eq!(a.position_key(), b.position_key());
+        assert_ne!(a.position_key(), c.position_key());
+
+        let mut seen: HashMap<PositionKey, u32> = HashMap::new();
+        for board in [&a, &b, &c] {
+            *seen.entry(board.position_key()).or_insert(0) += 1;
+        }
+        assert_eq!(Some(&2), seen.get(&a.position_key()));
+        assert_eq!(2, seen.len());
+    }
+
+    #[test]
+    fn test_position_hash_follows_moves_and_takebacks() {
+        let a = pgn_board("1. Nf3 Nf6 2. Nc3 Nc6");
+        let b = pgn_board("1. Nc3 Nc6 2. Nf3 Nf6");
+        let c = pgn_board("1. Nc3 Nf6 2. Nf3 Nc6 3. Ng1");
+        assert_eq!(a.position_hash(), b.position_hash());
+        assert_ne!(a.position_hash(), c.position_hash());
+
+        // Castling both ways, captures, en passant and promotions are all a move away
+        let mut board =
+            Board::from_fen("r3k2r/1P3ppp/8/3pP3/8/8/5PPP/R3K2R w KQkq d6 0 1").unwrap();
+        let original = board.position_hash();
+        for m in board.legal_moves() {
+            let undo = board.make_move(m);
+            assert_eq!(board.compute_hash(), board.position_hash());
+            assert_eq!(
+                Board::from_fen(&board.to_fen()).unwrap().position_hash(),
+                board.position_hash()
+            );
+            board.unmake_move(undo);
+            assert_eq!(original, board.position_hash());
+        }
+    }
+
+    #[test]
+    fn test_replay_move_list() {
+        let mut board = Board::new();
+        for san in ["e4", "d5", "exd5", "Qxd5", "Nc3"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        let replayed = Board::replay(&board.move_list).unwrap();
+        assert_eq!(board.to_fen(), replayed.to_fen());
+        assert_eq!(board.graveyard, replayed.graveyard);
+        assert_eq!(5, replayed.move_list.len());
+    }
+
+    #[test]
+    fn test_replay_reports_first_illegal_move() {
+        let mut board = Board::new();
+        for san in ["e4", "e5", "Nf3"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        // Dropping black's reply leaves white to play Nf3 out of turn
+        let mut moves = board.move_list.clone();
+        moves.remove(1);
+        assert_eq!(
+            Err((1, MoveError::WrongColorPiece)),
+            Board::replay(&moves).map(|b| b.to_fen())
+        );
+    }
+
+    #[test]
+    fn test_capture_is_stored_on_move() {
+        let mut board = Board::new();
+        for san in ["e4", "d5", "exd5"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        let capture = board.get_previous_move().unwrap();
+        let captured = capture.captured.unwrap();
+        assert_eq!(PieceType::Pawn, captured.piece_type);
+        assert_eq!(PieceColor::Black, captured.color);
+        assert_eq!(Some(PieceLoc::new(4, 3)), capture.captured_pos);
+    }
+
+    #[test]
+    fn test_en_passant_capture_is_stored_and_removed() {
+        let mut board = Board::new();
+        for san in ["e4", "a6", "e5", "d5", "exd6"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        let capture = board.get_previous_move().unwrap();
+        assert_eq!(PieceType::Pawn, capture.captured.unwrap().piece_type);
+        assert_eq!(Some(PieceLoc::new(4, 3)), capture.captured_pos);
+        assert!(board.get_piece_at_location(PieceLoc::new(4, 3)).is_none());
+        assert!(board.get_piece_at_location(PieceLoc::new(5, 3)).is_some());
+    }
+
+    #[test]
+    fn test_black_en_passant_captures_the_pawn_behind_the_target() {
+        let board = pgn_board("1. a3 d5 2. a4 d4 3. e4 dxe3");
+
+        let capture = board.get_previous_move().unwrap();
+        assert_eq!(PieceLoc::from_notation_opt("e4"), capture.captured_pos);
+        assert_eq!(PieceColor::White, capture.captured.unwrap().color);
+        assert!(board.get_piece_at_location(PieceLoc::new(3, 4)).is_none());
+        assert_eq!(1, board.captured_count(PieceColor::White, PieceType::Pawn));
+    }
+
+    #[test]
+    fn test_white_en_passant_removes_the_pawn_behind_the_target() {
+        let board = pgn_board("1. e4 a6 2. e5 d5 3. exd6");
+
+        let capture = board.get_previous_move().unwrap();
+        assert_eq!(MoveType::EnPassant, capture.move_type);
+        assert_eq!(PieceLoc::from_notation_opt("d5"), capture.captured_pos);
+        assert!(board.get_piece_at_location(PieceLoc::new(4, 3)).is_none());
+        assert_eq!(
+            Some(PieceColor::White),
+            board
+                .get_piece_at_location(PieceLoc::new(5, 3))
+                .map(|p| p.color)
+        );
+        assert_eq!(1, board.captured_pieces(PieceColor::Black).len());
+        assert_eq!(1, board.captured_count(PieceColor::Black, PieceType::Pawn));
+    }
+
+    #[test]
+    fn test_en_passant_without_a_pawn_to_capture_is_an_error() {
+        // The target square says a pawn just skipped over d6, but there is no pawn on d5
+        let mut board = Board::from_fen("4k3/8/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+        board.en_passant_target = PieceLoc::from_notation_opt("d6");
+        let pawn = board.get_piece_at_location(PieceLoc::new(4, 4)).unwrap();
+
+        assert_eq!(
+            Err(MoveError::NoPieceToCapture {
+                at: PieceLoc::new(4, 3)
+            }),
+            Move::new(&board, &pawn, &PieceLoc::new(4, 4), &PieceLoc::new(5, 3))
+        );
+        assert!(!board
+            .legal_moves()
+            .iter()
+            .any(|m| m.move_type == MoveType::EnPassant));
+    }
+
+    #[test]
+    fn test_display_start_position_cases_by_color() {
+        let display = Board::new().to_string();
+
+        assert!(display.starts_with(
+            "8 r n b q k b n r \n\
+             7 p p p p p p p p \n\
+             6 . . . . . . . . \n\
+             5 . . . . . . . . \n\
+             4 . . . . . . . . \n\
+             3 . . . . . . . . \n\
+             2 P P P P P P P P \n\
+             1 R N B Q K B N R \n\
+             \x20 a b c d e f g h\n"
+        ));
+    }
+
+    #[test]
+    fn test_string_oriented_for_black() {
+        let mut board = Board::new();
+        let m = Move::from_san(&board, "e4").unwrap();
+        board = board.move_piece(m).unwrap();
+
+        assert!(board.to_string_oriented(PieceColor::Black).starts_with(
+            "1 R N B K Q B N R \n\
+             2 P P P . P P P P \n\
+             3 . . . . . . . . \n\
+             4 . . . P . . . . \n\
+             5 . . . . . . . . \n\
+             6 . . . . . . . . \n\
+             7 p p p p p p p p \n\
+             8 r n b k q b n r \n\
+             \x20 h g f e d c b a\n"
+        ));
+        assert_eq!(
+            board.to_string(),
+            board.to_string_oriented(PieceColor::White)
+        );
+    }
+
+    #[test]
+    fn test_square_is_light() {
+        assert!(!board_display::square_is_light(PieceLoc::new(0, 0)));
+        assert!(board_display::square_is_light(PieceLoc::new(0, 7)));
+        assert!(board_display::square_is_light(PieceLoc::new(3, 4)));
+        assert!(!board_display::square_is_light(PieceLoc::new(7, 7)));
+    }
+
+    #[test]
+    fn test_ansi_escapes_only_in_ansi_string() {
+        let board = Board::new();
+        let ansi = board.to_ansi_string();
+
+        assert!(ansi.contains('\x1b'));
+        assert!(ansi.starts_with("8 \x1b[48;5;180m\x1b[1;30mR \x1b[0m"));
+        assert!(ansi.ends_with("  a b c d e f g h\n"));
+        assert!(!board.to_string().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_unicode_string_start_position() {
+        let board = Board::new();
+        let unicode = board.to_unicode_string();
+        let lines: Vec<&str> = unicode.lines().collect();
+
+        assert_eq!(9, lines.len());
+        assert_eq!("  a b c d e f g h", lines[8]);
+        assert_eq!("8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜ ", lines[0]);
+        assert_eq!(format!("7 {}", "♟ ".repeat(8)), lines[1]);
+        assert_eq!(format!("4 {}", ". ".repeat(8)), lines[4]);
+        assert_eq!(format!("2 {}", "♙ ".repeat(8)), lines[6]);
+        assert_eq!("1 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖ ", lines[7]);
+    }
+
+    #[test]
+    fn test_diagram_start_position() {
+        let expected = "  +---+---+---+---+---+---+---+---+
+8 | r | n | b | q | k | b | n | r |
+  +---+---+---+---+---+---+---+---+
+7 | p | p | p | p | p | p | p | p |
+  +---+---+---+---+---+---+---+---+
+6 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+5 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+4 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+3 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+2 | P | P | P | P | P | P | P | P |
+  +---+---+---+---+---+---+---+---+
+1 | R | N | B | Q | K | B | N | R |
+  +---+---+---+---+---+---+---+---+
+    a   b   c   d   e   f   g   h
+";
+        assert_eq!(expected, Board::new().to_diagram());
+    }
+
+    #[test]
+    fn test_diagram_pads_two_digit_ranks() {
+        let diagram = Board::with_size(10, 3).to_diagram();
+        let lines: Vec<&str> = diagram.lines().collect();
+
+        assert_eq!("   +---+---+---+", lines[0]);
+        assert!(lines[1].starts_with("10 |"));
+        assert!(lines[19].starts_with(" 1 |"));
+        assert_eq!("     a   b   c", lines[21]);
+    }
+
+    #[test]
+    fn test_movelist_display_uses_san() {
+        let mut board = Board::new();
+        for san in ["e4", "d5", "exd5", "Qxd5", "Nc3"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert_eq!(
+            "Moves:\n1. e4 d5 \n2. exd5 Qxd5 \n3. Nc3 ",
+            board_display::get_movelist_display(&board)
+        );
+    }
+
+    #[test]
+    fn test_movelist_display_numbers_from_the_start_position() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 30").unwrap();
+        for san in ["Kd7", "Kd2", "Kc6"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert_eq!(
+            "Moves:\n30... Kd7 \n31. Kd2 Kc6 ",
+            board_display::get_movelist_display(&board)
+        );
+    }
+
+    #[test]
+    fn test_display_after_moves_from_fen() {
+        // The knight on e5 stands on a square that is empty in the standard setup
+        let mut board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let fen = board.to_fen();
+        let m = Move::from_san(&board, "Nxf7").unwrap();
+        board = board.move_piece(m).unwrap();
+
+        assert!(board.to_string().contains("Moves:\n1. Nxf7 "));
+        assert_eq!(fen, board.start_position().to_fen());
+    }
+
+    #[test]
+    fn test_display_shows_halfmove_clock() {
+        let mut board = Board::new();
+        for san in ["Nf3", "Nf6", "Nc3"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+
+        assert!(board
+            .to_string()
+            .contains("\nHalfmove clock: 3/100 toward fifty-move draw\n"));
     }
 }
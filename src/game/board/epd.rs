@@ -0,0 +1,153 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use super::fen::FenError;
+use super::Board;
+
+#[derive(Debug, PartialEq)]
+pub enum EpdError {
+    WrongFieldCount,
+    InvalidPosition(FenError),
+    MalformedOperation,
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpdError::WrongFieldCount => write!(
+                f,
+                "Invalid EPD: A position must have four fields before its operations."
+            ),
+            EpdError::InvalidPosition(e) => write!(f, "Invalid EPD: {}", e),
+            EpdError::MalformedOperation => write!(
+                f,
+                "Invalid EPD: Each operation must be an opcode and its operands, ending in ';'."
+            ),
+        }
+    }
+}
+
+impl From<FenError> for EpdError {
+    fn from(e: FenError) -> Self {
+        EpdError::InvalidPosition(e)
+    }
+}
+
+// Splits the operations on their semicolons, except for those inside quoted strings
+fn split_operations(operations: &str) -> Result<Vec<&str>, EpdError> {
+    let mut split = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+
+    for (i, c) in operations.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                split.push(operations[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    if in_quotes || !operations[start..].trim().is_empty() {
+        return Err(EpdError::MalformedOperation);
+    }
+    Ok(split)
+}
+
+impl Board {
+    /// Builds a board from an Extended Position Description line, as used by engine test
+    /// suites, i.e. `r1b1k2r/... w KQkq - bm Nf3; id "WAC.001";`.
+    ///
+    /// The first four fields are read as in FEN, and the operations after them are
+    /// returned by opcode with any quotes around the operand removed, so a `bm` or `am`
+    /// operand can be handed straight to `Move::from_san`. The clocks come from the `hmvc`
+    /// and `fmvn` operations when given, and start fresh otherwise.
+    pub fn from_epd(epd: &str) -> Result<(Board, HashMap<String, String>), EpdError> {
+        let mut rest = epd.trim();
+        let mut fields: Vec<&str> = Vec::new();
+        for _ in 0..4 {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(EpdError::WrongFieldCount);
+            }
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+
+        let mut operations = HashMap::new();
+        for operation in split_operations(rest)? {
+            let (opcode, operand) = operation
+                .split_once(char::is_whitespace)
+                .unwrap_or((operation, ""));
+            if opcode.is_empty() {
+                return Err(EpdError::MalformedOperation);
+            }
+            let operand = operand.trim();
+            let operand = operand
+                .strip_prefix('"')
+                .and_then(|quoted| quoted.strip_suffix('"'))
+                .unwrap_or(operand);
+            operations.insert(opcode.to_string(), operand.to_string());
+        }
+
+        let halfmove_clock = operations.get("hmvc").map_or("0", String::as_str);
+        let fullmove_number = operations.get("fmvn").map_or("1", String::as_str);
+        let fen = format!(
+            "{} {} {}",
+            fields.join(" "),
+            halfmove_clock,
+            fullmove_number
+        );
+        Ok((Board::from_fen(&fen)?, operations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::moves::Move;
+
+    const WAC_001: &str =
+        r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4BK1 w - - bm Qg6; id "WAC.001";"#;
+
+    #[test]
+    fn test_from_epd_reads_operations() {
+        let (board, operations) = Board::from_epd(WAC_001).unwrap();
+
+        assert_eq!(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4BK1 w - - 0 1",
+            board.to_fen()
+        );
+        assert_eq!("WAC.001", operations["id"]);
+        assert!(Move::from_san(&board, &operations["bm"]).is_ok());
+    }
+
+    #[test]
+    fn test_from_epd_clocks_and_quoted_semicolons() {
+        let epd = r#"4k3/8/8/8/8/8/8/4K2R w K - am Kf2; c0 "a; b"; hmvc 12; fmvn 40;"#;
+        let (board, operations) = Board::from_epd(epd).unwrap();
+
+        assert_eq!(12, board.halfmove_clock);
+        assert_eq!(40, board.fullmove_number);
+        assert_eq!("a; b", operations["c0"]);
+        assert!(Move::from_san(&board, &operations["am"]).is_ok());
+    }
+
+    #[test]
+    fn test_from_epd_rejects_malformed_input() {
+        assert_eq!(
+            EpdError::WrongFieldCount,
+            Board::from_epd("8/8/8/8/8/8/8/8 w").unwrap_err()
+        );
+        assert_eq!(
+            EpdError::MalformedOperation,
+            Board::from_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Kd2").unwrap_err()
+        );
+        assert_eq!(
+            EpdError::InvalidPosition(FenError::InvalidActiveColor),
+            Board::from_epd("4k3/8/8/8/8/8/8/4K3 x - - id \"x\";").unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use super::Board;
+use crate::game::moves::{move_checker::MoveType, Move};
+use crate::game::piece::piece_info::{PieceColor, PieceLoc, PieceType};
+
+// Kings count for nothing in material, but in an exchange losing one outweighs everything
+const SEE_KING_VALUE: i32 = 20000;
+
+/// The weights `Board::evaluate_with` scores a position by, all in centipawns, so the
+/// engine can be tuned without editing the source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EvalParams {
+    pub pawn: i32,
+    pub knight: i32,
+    pub bishop: i32,
+    pub rook: i32,
+    pub queen: i32,
+    // Added for a side that still has two or more bishops
+    pub bishop_pair: i32,
+}
+
+impl EvalParams {
+    /// What a piece of the given type is worth in material. Kings are worth nothing, as
+    /// both sides always have one.
+    pub fn piece_value(&self, piece_type: PieceType) -> i32 {
+        match piece_type {
+            PieceType::Pawn => self.pawn,
+            PieceType::Knight => self.knight,
+            PieceType::Bishop => self.bishop,
+            PieceType::Rook => self.rook,
+            PieceType::Queen => self.queen,
+            PieceType::King => 0,
+        }
+    }
+}
+
+/// The classic 100/320/330/500/900 piece values from `PieceType::value`, with no bishop
+/// pair bonus.
+impl Default for EvalParams {
+    fn default() -> EvalParams {
+        EvalParams {
+            pawn: PieceType::Pawn.value() as i32,
+            knight: PieceType::Knight.value() as i32,
+            bishop: PieceType::Bishop.value() as i32,
+            rook: PieceType::Rook.value() as i32,
+            queen: PieceType::Queen.value() as i32,
+            bishop_pair: 0,
+        }
+    }
+}
+
+// Piece-square tables in centipawns, written from white's side with the eighth rank on
+// the top row, so the a8 square comes first. Black reads them mirrored top to bottom.
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+// Keeps the king tucked behind its pawns, ideally castled
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+fn piece_square_table(piece_type: PieceType) -> &'static [i32; 64] {
+    match piece_type {
+        PieceType::Pawn => &PAWN_TABLE,
+        PieceType::Knight => &KNIGHT_TABLE,
+        PieceType::Bishop => &BISHOP_TABLE,
+        PieceType::Rook => &ROOK_TABLE,
+        PieceType::Queen => &QUEEN_TABLE,
+        PieceType::King => &KING_TABLE,
+    }
+}
+
+// The bonus for a piece of the given color standing on the given square
+fn piece_square_value(piece_type: PieceType, color: PieceColor, loc: PieceLoc) -> i32 {
+    let row = match color {
+        PieceColor::White => 7 - loc.rank,
+        PieceColor::Black => loc.rank,
+    };
+    piece_square_table(piece_type)[usize::from(row) * 8 + usize::from(loc.file)]
+}
+
+impl Board {
+    /// Statically evaluates the position in centipawns from white's side: the material
+    /// balance plus a positional bonus from piece-square tables, which favour central
+    /// knights, advanced pawns and a sheltered king.
+    ///
+    /// The tables only describe an 8x8 board, so other sizes are scored on material alone.
+    pub fn evaluate(&self) -> i32 {
+        self.evaluate_with(&EvalParams::default())
+    }
+
+    /// Evaluates the position like `evaluate`, but weighs the material by `params`.
+    pub fn evaluate_with(&self, params: &EvalParams) -> i32 {
+        let positional = self.ranks == 8 && self.files == 8;
+        let score = |color: PieceColor| -> i32 {
+            let mut bishops = 0;
+            let mut score = 0;
+            for (loc, piece) in self.pieces_of_color(color) {
+                score += params.piece_value(piece.piece_type);
+                if positional {
+                    score += piece_square_value(piece.piece_type, color, loc);
+                }
+                if piece.piece_type == PieceType::Bishop {
+                    bishops += 1;
+                }
+            }
+            if bishops >= 2 {
+                score += params.bishop_pair;
+            }
+            score
+        };
+        score(PieceColor::White) - score(PieceColor::Black)
+    }
+
+    /// Static Exchange Evaluation: the material the side making `m` wins or loses, in
+    /// centipawns, once every capture and recapture on the target square is played out.
+    ///
+    /// Each side recaptures with its least valuable attacker and may stop whenever going
+    /// on would lose more. Attackers hidden behind the pieces that capture first join in
+    /// as the square is cleared, but pins and checks elsewhere on the board are ignored.
+    pub fn see(&self, m: &Move) -> i32 {
+        let target = m.end_pos;
+        let mut board = self.clone();
+        board.set_piece(m.start_pos, None);
+        board.set_piece(target, Some(m.piece));
+
+        // gains[i] is what the side making capture i has won if the exchange stops there
+        let mut gains = vec![m.captured.map_or(0, |piece| see_value(piece.piece_type))];
+        let mut on_target = see_value(m.piece.piece_type);
+        let mut side = m.piece.color.flip();
+
+        while let Some((loc, piece)) = board
+            .attackers_of(target, side)
+            .into_iter()
+            .filter_map(|loc| Some((loc, board.get_piece_at_location(loc)?)))
+            .min_by_key(|(_, piece)| see_value(piece.piece_type))
+        {
+            gains.push(on_target - gains[gains.len() - 1]);
+            board.set_piece(loc, None);
+            board.set_piece(target, Some(piece));
+            on_target = see_value(piece.piece_type);
+            side = side.flip();
+        }
+
+        // Work back from the last capture, letting each side stop if recapturing loses
+        for i in (1..gains.len()).rev() {
+            gains[i - 1] = -(-gains[i - 1]).max(gains[i]);
+        }
+        gains[0]
+    }
+
+    /// The squares of `color`'s pieces that the opponent could win material by capturing,
+    /// either because nothing defends them or because the exchange still comes out ahead
+    /// for the attacker. Kings are never listed, as attacks on them are checks.
+    pub fn hanging_pieces(&self, color: PieceColor) -> Vec<PieceLoc> {
+        self.pieces_of_color(color)
+            .filter(|(_, piece)| piece.piece_type != PieceType::King)
+            .filter(|&(loc, piece)| {
+                self.attackers_of(loc, color.flip())
+                    .into_iter()
+                    .filter_map(|attacker_loc| {
+                        let attacker = self.get_piece_at_location(attacker_loc)?;
+                        Some(Move {
+                            piece: attacker,
+                            start_pos: attacker_loc,
+                            end_pos: loc,
+                            move_type: MoveType::Normal,
+                            capturing: true,
+                            promotion: None,
+                            captured: Some(piece),
+                            captured_pos: Some(loc),
+                        })
+                    })
+                    .any(|capture| self.see(&capture) > 0)
+            })
+            .map(|(loc, _)| loc)
+            .collect()
+    }
+}
+
+fn see_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::King => SEE_KING_VALUE,
+        _ => piece_type.value() as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::Piece;
+
+    fn see_of(fen: &str, san: &str) -> i32 {
+        let board = Board::from_fen(fen).unwrap();
+        board.see(&Move::from_san(&board, san).unwrap())
+    }
+
+    #[test]
+    fn test_see_undefended_and_defended_pawns() {
+        // A free pawn, then the same pawn defended by another
+        assert_eq!(100, see_of("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "exd5"));
+        assert_eq!(0, see_of("4k3/8/2p5/3p4/4P3/8/8/4K3 w - - 0 1", "exd5"));
+        // A knight taking a defended pawn loses the knight for it
+        assert_eq!(
+            100 - 320,
+            see_of("4k3/8/2p5/3p4/8/4N3/8/4K3 w - - 0 1", "Nxd5")
+        );
+    }
+
+    #[test]
+    fn test_see_counts_attackers_behind_the_first() {
+        // The rooks double up on the e-file against a pawn defended only by the bishop,
+        // so the front rook's loss is won back by the one behind it
+        let fen = "4k3/8/3b4/4p3/8/8/4R3/4RK2 w - - 0 1";
+        assert_eq!(100 - 500 + 330, see_of(fen, "Rxe5"));
+        // Defended twice, the exchange is no longer worth starting
+        let fen = "4k3/8/3b1p2/4p3/8/8/4R3/4RK2 w - - 0 1";
+        assert_eq!(100 - 500, see_of(fen, "Rxe5"));
+    }
+
+    #[test]
+    fn test_see_king_cannot_recapture_into_a_defended_square() {
+        // Alone, the rook is lost to the king for a pawn
+        assert_eq!(
+            100 - 500,
+            see_of("4k3/4p3/8/8/8/8/8/4R1K1 w - - 0 1", "Rxe7+")
+        );
+        // With a second rook behind it, the king can't take back
+        assert_eq!(100, see_of("4k3/4p3/8/8/8/8/4R3/4R1K1 w - - 0 1", "Rxe7+"));
+    }
+
+    #[test]
+    fn test_hanging_pieces() {
+        // The knight on e5 has no defender, and the bishop on c4 is attacked by a pawn.
+        // The black bishop also attacks the c1 bishop and h2 pawn, but both are defended.
+        let board =
+            Board::from_fen("r2qkb1r/ppp2ppp/2n5/3pN3/2B2b2/8/PPP2PPP/RNBQK2R w KQkq - 0 1")
+                .unwrap();
+        let hanging: Vec<String> = board
+            .hanging_pieces(PieceColor::White)
+            .iter()
+            .map(|loc| loc.to_string())
+            .collect();
+
+        assert_eq!(vec!["c4", "e5"], hanging);
+        assert!(Board::new().hanging_pieces(PieceColor::White).is_empty());
+    }
+
+    fn board_with_white_knight(loc: PieceLoc) -> Board {
+        Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 6),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // G1
+            .with_piece(
+                PieceLoc::new(7, 6),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ) // G8
+            .with_piece(loc, Piece::new(PieceType::Knight, PieceColor::White))
+    }
+
+    #[test]
+    fn test_evaluate_start_position_is_level() {
+        assert_eq!(0, Board::new().evaluate());
+    }
+
+    #[test]
+    fn test_evaluate_prefers_central_knight() {
+        let central = board_with_white_knight(PieceLoc::new(3, 4)); // E4
+        let cornered = board_with_white_knight(PieceLoc::new(0, 0)); // A1
+
+        assert_eq!(central.material_balance(), cornered.material_balance());
+        assert!(central.evaluate() > cornered.evaluate());
+    }
+
+    #[test]
+    fn test_evaluate_with_weighs_material_by_params() {
+        // A knight against a bishop, which the classic values rate slightly in black's favour
+        let board = board_with_white_knight(PieceLoc::new(3, 4)).with_piece(
+            PieceLoc::new(4, 3),
+            Piece::new(PieceType::Bishop, PieceColor::Black),
+        );
+        let default = EvalParams::default();
+        let strong_knights = EvalParams {
+            knight: 400,
+            ..default
+        };
+
+        assert_eq!(board.evaluate(), board.evaluate_with(&default));
+        assert_eq!(
+            board.evaluate_with(&default) + 80,
+            board.evaluate_with(&strong_knights)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with_bishop_pair_bonus() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        let params = EvalParams {
+            bishop_pair: 50,
+            ..EvalParams::default()
+        };
+
+        assert_eq!(board.evaluate() + 50, board.evaluate_with(&params));
+    }
+
+    #[test]
+    fn test_evaluate_mirrors_tables_for_black() {
+        assert_eq!(
+            piece_square_value(PieceType::Pawn, PieceColor::White, PieceLoc::new(6, 0)),
+            piece_square_value(PieceType::Pawn, PieceColor::Black, PieceLoc::new(1, 0))
+        );
+        assert_eq!(
+            piece_square_value(PieceType::King, PieceColor::White, PieceLoc::new(0, 6)),
+            piece_square_value(PieceType::King, PieceColor::Black, PieceLoc::new(7, 6))
+        );
+    }
+}
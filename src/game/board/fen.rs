@@ -0,0 +1,522 @@
+use core::fmt;
+
+use super::{Board, CastlingRights};
+use crate::game::board::board_display;
+use crate::game::piece::{
+    piece_info::{PieceColor, PieceLoc, PieceType},
+    Piece,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum FenError {
+    WrongFieldCount,
+    WrongRankCount,
+    MalformedRank,
+    InvalidPieceChar,
+    InvalidKings,
+    InvalidActiveColor,
+    InvalidCastlingRights,
+    InvalidEnPassantSquare,
+    InvalidClock,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match *self {
+            FenError::WrongFieldCount => "A FEN string must have exactly six fields.",
+            FenError::WrongRankCount => "The piece placement must describe exactly eight ranks.",
+            FenError::MalformedRank => "Each rank must describe exactly eight squares.",
+            FenError::InvalidPieceChar => "The piece placement contains an unknown piece.",
+            FenError::InvalidKings => {
+                "Each side must have exactly one king, and the kings can't stand side by side."
+            }
+            FenError::InvalidActiveColor => "The active color must be either 'w' or 'b'.",
+            FenError::InvalidCastlingRights => {
+                "Castling rights must be '-' or some of 'KQkq' or rook file letters, matching an unmoved king and rook."
+            }
+            FenError::InvalidEnPassantSquare => {
+                "The en passant target must be '-' or a square on the board behind a pawn that just moved two squares."
+            }
+            FenError::InvalidClock => "The move clocks must be non-negative whole numbers.",
+        };
+        write!(f, "Invalid FEN: {}", output)
+    }
+}
+
+fn piece_from_char(c: char) -> Option<Piece> {
+    let color = if c.is_ascii_uppercase() {
+        PieceColor::White
+    } else {
+        PieceColor::Black
+    };
+    let piece_type = PieceType::from_char(c.to_ascii_uppercase())?;
+    Some(Piece::new(piece_type, color))
+}
+
+impl Board {
+    /// Builds a board from a Forsyth-Edwards Notation string, i.e.
+    /// "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".
+    ///
+    /// FEN doesn't record which pieces have moved, so `has_moved` is inferred: pawns off
+    /// their starting rank have moved, and kings and rooks have moved unless a castling
+    /// right says otherwise. The move list and graveyard start out empty.
+    ///
+    /// For Chess960 the castling rights may also name the rook's file, as in Shredder-FEN
+    /// ("HAha") or X-FEN, where "K" and "Q" stand for the outermost rook on each side.
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::WrongFieldCount);
+        }
+
+        let mut board = Board::empty(8, 8);
+        board.parse_placement(fields[0])?;
+        board.check_kings()?;
+        board.current_turn = match fields[1] {
+            "w" => PieceColor::White,
+            "b" => PieceColor::Black,
+            _ => return Err(FenError::InvalidActiveColor),
+        };
+        board.parse_castling_rights(fields[2])?;
+        board.en_passant_target = board.parse_en_passant_target(fields[3])?;
+        board.halfmove_clock = fields[4].parse().map_err(|_| FenError::InvalidClock)?;
+        board.fullmove_number = match fields[5].parse() {
+            Ok(number) if number > 0 => number,
+            _ => return Err(FenError::InvalidClock),
+        };
+
+        Ok(board.rehashed())
+    }
+
+    /// Serializes the board as a Forsyth-Edwards Notation string, the inverse of `from_fen`.
+    ///
+    /// Castling rights come from the rights stored on the board, so a right lost to a
+    /// captured rook stays lost.
+    pub fn to_fen(&self) -> String {
+        let mut rows: Vec<String> = Vec::new();
+        for rank in (0..self.ranks).rev() {
+            let mut row = String::new();
+            let mut empty_squares = 0;
+
+            for file in 0..self.files {
+                match self.get_piece_at_location(PieceLoc::new(rank, file)) {
+                    Some(piece) => {
+                        if empty_squares > 0 {
+                            row.push_str(&empty_squares.to_string());
+                            empty_squares = 0;
+                        }
+                        row.push(board_display::get_piece_char_cased(&piece));
+                    }
+                    None => empty_squares += 1,
+                }
+            }
+            if empty_squares > 0 {
+                row.push_str(&empty_squares.to_string());
+            }
+            rows.push(row);
+        }
+
+        let active_color = match self.current_turn {
+            PieceColor::White => "w",
+            PieceColor::Black => "b",
+        };
+
+        let en_passant = match self.en_passant_target {
+            Some(target) => target.to_string(),
+            None => String::from("-"),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            rows.join("/"),
+            active_color,
+            self.castling_rights_fen(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+
+    // Rights are written as "KQkq" when the rook is the outermost one on its side of the
+    // king, and otherwise by the rook's file letter, as X-FEN does
+    fn castling_rights_fen(&self) -> String {
+        let mut rights = String::new();
+        for (right, color, kingside) in [
+            ('K', PieceColor::White, true),
+            ('Q', PieceColor::White, false),
+            ('k', PieceColor::Black, true),
+            ('q', PieceColor::Black, false),
+        ] {
+            let Some(rook_file) = self.castling_rook_file(color, kingside) else {
+                continue;
+            };
+            if self.outermost_rook_file(color, kingside) == Some(rook_file) {
+                rights.push(right);
+            } else {
+                let file = board_display::file_name(rook_file);
+                match color {
+                    PieceColor::White => rights.push_str(&file.to_ascii_uppercase()),
+                    PieceColor::Black => rights.push_str(&file),
+                }
+            }
+        }
+
+        if rights.is_empty() {
+            rights.push('-');
+        }
+        rights
+    }
+
+    // The file of the rook of `color` furthest from its king on the back rank, on one side
+    fn outermost_rook_file(&self, color: PieceColor, kingside: bool) -> Option<u8> {
+        let back_rank = self.back_rank(color);
+        let is_piece = |file: u8, piece_type: PieceType| {
+            self.get_piece_at_location(PieceLoc::new(back_rank, file))
+                .is_some_and(|p| p.piece_type == piece_type && p.color == color)
+        };
+        let king_file = (0..self.files).find(|&file| is_piece(file, PieceType::King))?;
+        let is_rook = |file: &u8| is_piece(*file, PieceType::Rook);
+        if kingside {
+            (king_file + 1..self.files).rev().find(is_rook)
+        } else {
+            (0..king_file).find(is_rook)
+        }
+    }
+
+    // Ranks are listed from the eighth down to the first, with digits counting empty squares
+    fn parse_placement(&mut self, placement: &str) -> Result<(), FenError> {
+        let rows: Vec<&str> = placement.split('/').collect();
+        if rows.len() != usize::from(self.ranks) {
+            return Err(FenError::WrongRankCount);
+        }
+
+        for (row_idx, row) in rows.iter().enumerate() {
+            let rank = self.ranks - 1 - row_idx as u8;
+            let mut file: u8 = 0;
+
+            for c in row.chars() {
+                if let Some(empty_squares) = c.to_digit(10) {
+                    // A long run of digits could otherwise wrap around past the last file
+                    file = file
+                        .checked_add(empty_squares as u8)
+                        .filter(|&file| file <= self.files)
+                        .ok_or(FenError::MalformedRank)?;
+                } else {
+                    let mut piece = piece_from_char(c).ok_or(FenError::InvalidPieceChar)?;
+                    if file >= self.files {
+                        return Err(FenError::MalformedRank);
+                    }
+
+                    piece.has_moved = match (piece.piece_type, piece.color) {
+                        (PieceType::Pawn, PieceColor::White) => rank != 1,
+                        (PieceType::Pawn, PieceColor::Black) => rank != self.ranks - 2,
+                        // Cleared again when parsing castling rights
+                        (PieceType::King | PieceType::Rook, _) => true,
+                        _ => false,
+                    };
+                    let idx = self.get_board_index_from_loc(PieceLoc::new(rank, file));
+                    self.board[idx] = Some(piece);
+                    file += 1;
+                }
+            }
+
+            if file != self.files {
+                return Err(FenError::MalformedRank);
+            }
+        }
+        Ok(())
+    }
+
+    // Every castling right is stored on the board and marks its king and rook as unmoved,
+    // and both must be in place. A right is either one of "KQkq", naming the outermost rook
+    // on that side, or the rook's file letter, uppercase for white.
+    fn parse_castling_rights(&mut self, rights: &str) -> Result<(), FenError> {
+        if rights == "-" {
+            return Ok(());
+        }
+
+        for right in rights.chars() {
+            let color = if right.is_ascii_uppercase() {
+                PieceColor::White
+            } else {
+                PieceColor::Black
+            };
+            let back_rank = self.back_rank(color);
+            let king_file = (0..self.files)
+                .find(|&file| {
+                    self.get_piece_at_location(PieceLoc::new(back_rank, file))
+                        .is_some_and(|p| p.piece_type == PieceType::King && p.color == color)
+                })
+                .ok_or(FenError::InvalidCastlingRights)?;
+
+            let (rook_file, kingside) = match right.to_ascii_uppercase() {
+                'K' => (self.outermost_rook_file(color, true), true),
+                'Q' => (self.outermost_rook_file(color, false), false),
+                letter => {
+                    let file = board_display::convert_rank_alpha_to_numeric(letter)
+                        .filter(|&file| file < self.files && file != king_file)
+                        .ok_or(FenError::InvalidCastlingRights)?;
+                    (Some(file), file > king_file)
+                }
+            };
+            let rook_file = rook_file.ok_or(FenError::InvalidCastlingRights)?;
+
+            for (file, piece_type) in [(king_file, PieceType::King), (rook_file, PieceType::Rook)] {
+                let idx = self.get_board_index_from_loc(PieceLoc::new(back_rank, file));
+                match &mut self.board[idx] {
+                    Some(piece) if piece.piece_type == piece_type && piece.color == color => {
+                        piece.has_moved = false;
+                    }
+                    _ => return Err(FenError::InvalidCastlingRights),
+                }
+            }
+
+            let stored = self
+                .castling_rights
+                .entry(color)
+                .or_insert(CastlingRights::none());
+            if kingside {
+                stored.kingside = Some(rook_file);
+            } else {
+                stored.queenside = Some(rook_file);
+            }
+        }
+        Ok(())
+    }
+
+    // Positions without both kings, or with the kings touching, can never come up in a game
+    fn check_kings(&self) -> Result<(), FenError> {
+        let kings = |color: PieceColor| -> Vec<PieceLoc> {
+            self.pieces_of_color(color)
+                .filter(|(_, piece)| piece.piece_type == PieceType::King)
+                .map(|(loc, _)| loc)
+                .collect()
+        };
+
+        match (
+            kings(PieceColor::White).as_slice(),
+            kings(PieceColor::Black).as_slice(),
+        ) {
+            ([white], [black])
+                if white.rank.abs_diff(black.rank) > 1 || white.file.abs_diff(black.file) > 1 =>
+            {
+                Ok(())
+            }
+            _ => Err(FenError::InvalidKings),
+        }
+    }
+
+    // The target is the square a pawn of the side that just moved skipped over, so the sixth
+    // rank when white is to move and the third when black is
+    fn parse_en_passant_target(&self, target: &str) -> Result<Option<PieceLoc>, FenError> {
+        if target == "-" {
+            return Ok(None);
+        }
+
+        let mut chars = target.chars();
+        let expected_rank = match self.current_turn {
+            PieceColor::White => '6',
+            PieceColor::Black => '3',
+        };
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None)
+                if file.is_ascii_lowercase() && rank == expected_rank =>
+            {
+                let file = board_display::convert_rank_alpha_to_numeric(file)
+                    .filter(|&file| file < self.files)
+                    .ok_or(FenError::InvalidEnPassantSquare)?;
+                let rank = match self.current_turn {
+                    PieceColor::White => self.ranks - 3,
+                    PieceColor::Black => 2,
+                };
+                Ok(Some(PieceLoc::new(rank, file)))
+            }
+            _ => Err(FenError::InvalidEnPassantSquare),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::moves::{move_checker::MoveType, Move};
+
+    const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    #[test]
+    fn test_from_fen_start_position_matches_new_board() {
+        let board = Board::from_fen(START_FEN).unwrap();
+        let expected = Board::new();
+
+        assert_eq!(expected.board, board.board);
+        assert_eq!(expected.current_turn, board.current_turn);
+        assert_eq!(expected.halfmove_clock, board.halfmove_clock);
+        assert_eq!(expected.fullmove_number, board.fullmove_number);
+        assert!(board.en_passant_target.is_none());
+        assert!(board.move_list.is_empty());
+    }
+
+    #[test]
+    fn test_from_fen_reads_side_clocks_and_en_passant() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+
+        assert_eq!(PieceColor::White, board.current_turn);
+        assert_eq!(0, board.halfmove_clock);
+        assert_eq!(3, board.fullmove_number);
+        assert_eq!(Some(PieceLoc::new(5, 5)), board.en_passant_target);
+
+        let en_passant = Move::from_san(&board, "exf6").unwrap();
+        assert_eq!(MoveType::EnPassant, en_passant.move_type);
+        // Pawns off their starting rank can no longer make a two-square move
+        assert!(board.board[36].unwrap().has_moved);
+    }
+
+    #[test]
+    fn test_from_fen_without_castling_rights_marks_king_moved() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 5 20").unwrap();
+
+        assert_eq!(5, board.halfmove_clock);
+        assert_eq!(20, board.fullmove_number);
+        assert!(!board.board[4].unwrap().has_moved);
+        assert!(!board.board[7].unwrap().has_moved);
+        assert!(board.board[0].unwrap().has_moved);
+        assert!(!board.board[56].unwrap().has_moved);
+        assert!(board.board[63].unwrap().has_moved);
+    }
+
+    #[test]
+    fn test_loaded_position_without_castling_rights_stays_uncastleable() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap();
+        assert!(Move::from_san(&board, "O-O").is_err());
+        assert!(Move::from_san(&board, "O-O-O").is_err());
+
+        // Taking back a king move mustn't reset the king to unmoved
+        let king_move = Move::from_san(&board, "Kf1").unwrap();
+        let undo = board.make_move(king_move);
+        assert!(board.get_previous_move().unwrap().piece.has_moved);
+        board.unmake_move(undo);
+
+        assert!(board.board[4].unwrap().has_moved);
+        assert!(Move::from_san(&board, "O-O").is_err());
+        assert_eq!("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1", board.to_fen());
+    }
+
+    #[test]
+    fn test_to_fen_start_position() {
+        assert_eq!(START_FEN, Board::new().to_fen());
+    }
+
+    #[test]
+    fn test_castling_rights_by_rook_file() {
+        // Shredder-FEN names every rook by its file
+        let board = Board::from_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1").unwrap();
+        assert_eq!(Some(6), board.castling_rook_file(PieceColor::White, true));
+        assert_eq!(Some(1), board.castling_rook_file(PieceColor::Black, false));
+        assert_eq!("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1", board.to_fen());
+
+        // X-FEN only names a rook by its file when it isn't the outermost on its side
+        let fen = "4k3/8/8/8/8/8/8/RR2K2R w KB - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(Some(1), board.castling_rook_file(PieceColor::White, false));
+        assert_eq!(fen, board.to_fen());
+
+        assert_eq!(
+            FenError::InvalidCastlingRights,
+            Board::from_fen("4k3/8/8/8/8/8/8/RR2K2R w C - 0 1").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_to_fen_after_moves_tracks_en_passant_and_castling() {
+        let mut board = Board::new();
+        for san in ["e4", "e5", "Ke2"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        assert_eq!(
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 1 2",
+            board.to_fen()
+        );
+
+        let m = Move::from_san(&board, "d5").unwrap();
+        let board = board.move_piece(m).unwrap();
+        assert_eq!(
+            "rnbqkbnr/ppp2ppp/8/3pp3/4P3/8/PPPPKPPP/RNBQ1BNR w kq d6 0 3",
+            board.to_fen()
+        );
+        assert_eq!(
+            board.to_fen(),
+            Board::from_fen(&board.to_fen()).unwrap().to_fen()
+        );
+    }
+
+    #[test]
+    fn test_to_fen_drops_right_when_rook_is_captured() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let m = Move::from_san(&board, "Rxh8+").unwrap();
+        let board = board.move_piece(m).unwrap();
+
+        assert_eq!("r3k2R/8/8/8/8/8/8/R3K3 b Qq - 0 1", board.to_fen());
+    }
+
+    #[test]
+    fn test_from_fen_rejects_malformed_input() {
+        assert_eq!(
+            FenError::WrongFieldCount,
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap_err()
+        );
+        assert_eq!(
+            FenError::WrongRankCount,
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap_err()
+        );
+        assert_eq!(
+            FenError::MalformedRank,
+            Board::from_fen("rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap_err()
+        );
+        assert_eq!(
+            FenError::MalformedRank,
+            Board::from_fen("99999999999999999999999999999/8/8/8/8/8/8/8 w - - 0 1").unwrap_err()
+        );
+        assert_eq!(
+            FenError::InvalidPieceChar,
+            Board::from_fen("rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap_err()
+        );
+        assert_eq!(
+            FenError::InvalidActiveColor,
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1")
+                .unwrap_err()
+        );
+        assert_eq!(
+            FenError::InvalidCastlingRights,
+            Board::from_fen("rnbqkbn1/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap_err()
+        );
+        assert_eq!(
+            FenError::InvalidEnPassantSquare,
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - z6 0 1").unwrap_err()
+        );
+        assert_eq!(
+            FenError::InvalidEnPassantSquare,
+            Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1").unwrap_err()
+        );
+        assert_eq!(
+            FenError::InvalidEnPassantSquare,
+            Board::from_fen("4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_from_fen_rejects_impossible_kings() {
+        for fen in [
+            "8/8/8/8/8/8/8/8 w - - 0 1",
+            "4k3/8/8/8/8/8/8/8 w - - 0 1",
+            "3kk3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "8/8/8/8/8/8/3k4/4K3 w - - 0 1",
+        ] {
+            assert_eq!(FenError::InvalidKings, Board::from_fen(fen).unwrap_err());
+        }
+    }
+}
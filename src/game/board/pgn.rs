@@ -0,0 +1,328 @@
+use core::fmt;
+
+use super::{fen::FenError, Board};
+use crate::game::moves::{move_checker::MoveError, san::SanError, Move};
+use crate::game::piece::piece_info::PieceColor;
+use crate::game::status::GameStatus;
+
+#[derive(Debug, PartialEq)]
+pub enum PgnError {
+    UnclosedComment,
+    UnclosedVariation,
+    InvalidMove(SanError),
+    IllegalMove(MoveError),
+    InvalidSetUp(FenError),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::UnclosedComment => write!(f, "Invalid PGN: A comment is missing its '}}'."),
+            PgnError::UnclosedVariation => {
+                write!(f, "Invalid PGN: A variation is missing its ')'.")
+            }
+            PgnError::InvalidMove(e) => write!(f, "Invalid PGN: {}", e),
+            PgnError::IllegalMove(e) => write!(f, "Invalid PGN: {}", e),
+            PgnError::InvalidSetUp(e) => write!(f, "Invalid PGN: {}", e),
+        }
+    }
+}
+
+const RESULT_TOKENS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// The value of the first tag pair with the given name, i.e. "Paris" for `[Event "Paris"]`
+fn tag_value<'a>(pgn: &'a str, name: &str) -> Option<&'a str> {
+    pgn.lines().find_map(|line| {
+        let pair = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+        let (tag, value) = pair.split_once(char::is_whitespace)?;
+        (tag == name).then(|| value.trim().trim_matches('"'))
+    })
+}
+
+// Reduces the movetext to its moves and result, dropping tag pairs, comments, variations,
+// move numbers and NAGs
+fn movetext_tokens(pgn: &str) -> Result<Vec<String>, PgnError> {
+    let mut movetext = String::new();
+    let mut in_comment = false;
+    let mut in_line_comment = false;
+    let mut variation_depth = 0;
+
+    for line in pgn.lines() {
+        if !in_comment && line.trim_start().starts_with('[') {
+            continue;
+        }
+        for c in line.chars() {
+            match c {
+                _ if in_line_comment => (),
+                '}' if in_comment => in_comment = false,
+                _ if in_comment => (),
+                '{' => in_comment = true,
+                ';' => in_line_comment = true,
+                '(' => variation_depth += 1,
+                ')' if variation_depth > 0 => variation_depth -= 1,
+                _ if variation_depth > 0 => (),
+                _ => movetext.push(c),
+            }
+        }
+        // Comments and variations stand between tokens, as does the end of a line
+        in_line_comment = false;
+        movetext.push(' ');
+    }
+
+    if in_comment {
+        return Err(PgnError::UnclosedComment);
+    }
+    if variation_depth > 0 {
+        return Err(PgnError::UnclosedVariation);
+    }
+
+    Ok(movetext
+        .split_whitespace()
+        .map(|token| {
+            if RESULT_TOKENS.contains(&token) {
+                return token;
+            }
+            // Move numbers may be glued to their move, i.e. "1.e4" or "3...Nf6". Only digits
+            // followed by dots make a number, as castling may be written "0-0".
+            let number = token.trim_start_matches(|c: char| c.is_ascii_digit());
+            if number.starts_with('.') {
+                number.trim_start_matches('.')
+            } else {
+                token
+            }
+        })
+        .filter(|token| {
+            !token.is_empty()
+                && !token.starts_with('$')
+                && !token.chars().all(|c| c == '!' || c == '?')
+        })
+        .map(String::from)
+        .collect())
+}
+
+impl Board {
+    /// Plays out a game from Portable Game Notation, the inverse of `to_pgn`.
+    ///
+    /// Tag pairs, comments, variations, NAGs like "$1" and annotations like "!?" are all
+    /// skipped over, and the moves are read as SAN from the position in the FEN tag, or
+    /// the standard starting position without one. Reading stops at the result token, if
+    /// there is one.
+    pub fn from_pgn(pgn: &str) -> Result<Board, PgnError> {
+        let mut board = match tag_value(pgn, "FEN") {
+            Some(fen) => Board::from_fen(fen).map_err(PgnError::InvalidSetUp)?,
+            None => Board::new(),
+        };
+        for token in movetext_tokens(pgn)? {
+            if RESULT_TOKENS.contains(&token.as_str()) {
+                break;
+            }
+            let m = Move::from_san(&board, &token).map_err(PgnError::InvalidMove)?;
+            board = board.move_piece(m).map_err(PgnError::IllegalMove)?;
+        }
+        Ok(board)
+    }
+
+    /// Exports the game as Portable Game Notation: the Seven Tag Roster followed by the
+    /// movetext, i.e. "1. e4 e5 2. Nf3 Nc6 *".
+    ///
+    /// The move list is replayed from the board's start position to produce the SAN for
+    /// each move. A game that didn't start from the standard position gets the SetUp and
+    /// FEN tags, and its move numbers carry on from the FEN's. The tags that a board can't
+    /// know about are filled with "?".
+    pub fn to_pgn(&self) -> String {
+        let result = match self.game_status() {
+            GameStatus::Checkmate {
+                winner: PieceColor::White,
+            }
+            | GameStatus::Resignation {
+                winner: PieceColor::White,
+            }
+            | GameStatus::Timeout {
+                winner: PieceColor::White,
+            } => "1-0",
+            GameStatus::Checkmate {
+                winner: PieceColor::Black,
+            }
+            | GameStatus::Resignation {
+                winner: PieceColor::Black,
+            }
+            | GameStatus::Timeout {
+                winner: PieceColor::Black,
+            } => "0-1",
+            GameStatus::Draw {
+                claimable: false, ..
+            }
+            | GameStatus::DrawAgreed
+            | GameStatus::DrawClaimed { .. } => "1/2-1/2",
+            // A board can't know whether a claimable draw was claimed, so the game goes on
+            GameStatus::Draw {
+                claimable: true, ..
+            }
+            | GameStatus::Ongoing => "*",
+        };
+
+        let start = self.start_position();
+        let start_fen = start.to_fen();
+        let mut tags = vec![
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", result),
+        ];
+        if start_fen != Board::new().to_fen() {
+            tags.push(("SetUp", "1"));
+            tags.push(("FEN", &start_fen));
+        }
+
+        let mut pgn = String::new();
+        for (tag, value) in tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", tag, value));
+        }
+        pgn.push('\n');
+
+        let mut movetext: Vec<String> = Vec::new();
+        for (label, san) in self.numbered_san_history() {
+            movetext.extend(label);
+            movetext.push(san);
+        }
+        movetext.push(result.to_string());
+
+        pgn.push_str(&movetext.join(" "));
+        pgn.push('\n');
+        pgn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(sans: &[&str]) -> Board {
+        let mut board = Board::new();
+        for san in sans {
+            let m = Move::from_san(&board, san).expect("Setup move should be legal");
+            board = board.move_piece(m).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn test_to_pgn_ongoing_game_with_castling() {
+        let board = play(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"]);
+        let pgn = board.to_pgn();
+
+        assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n"));
+        assert!(pgn.contains("[Result \"*\"]\n\n"));
+        assert!(pgn.ends_with("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O *\n"));
+    }
+
+    #[test]
+    fn test_to_pgn_checkmate_result() {
+        let board = play(&["f3", "e5", "g4", "Qh4"]);
+        let pgn = board.to_pgn();
+
+        assert!(pgn.contains("[Result \"0-1\"]"));
+        assert!(pgn.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+    }
+
+    #[test]
+    fn test_to_pgn_unclaimed_draw_is_ongoing() {
+        let board = play(&["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"]);
+        assert!(board.to_pgn().contains("[Result \"*\"]"));
+
+        // Fivefold repetition needs no claim
+        let board = play(&[
+            "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8",
+            "Nf3", "Nf6", "Ng1", "Ng8",
+        ]);
+        assert!(board.to_pgn().contains("[Result \"1/2-1/2\"]"));
+    }
+
+    #[test]
+    fn test_to_pgn_from_fen_start() {
+        let mut board = Board::from_fen("8/4k3/8/r7/8/8/8/R3K3 b - - 0 40").unwrap();
+        for san in ["Rxa1+", "Kd2", "Ra2+"] {
+            let m = Move::from_san(&board, san).unwrap();
+            board = board.move_piece(m).unwrap();
+        }
+        let pgn = board.to_pgn();
+
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"8/4k3/8/r7/8/8/8/R3K3 b - - 0 40\"]\n\n"));
+        assert!(pgn.ends_with("40... Rxa1+ 41. Kd2 Ra2+ *\n"));
+        assert!(!Board::new().to_pgn().contains("[FEN "));
+
+        let imported = Board::from_pgn(&pgn).unwrap();
+        assert_eq!(board.to_fen(), imported.to_fen());
+        assert_eq!(
+            PgnError::InvalidSetUp(FenError::WrongFieldCount),
+            Board::from_pgn("[FEN \"8/8/8 w\"]\n\n*").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_from_pgn_opera_game() {
+        let pgn = r#"[Event "Paris"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 $6 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7
+8. Nc3 c6 9. Bg5 {Black is in what's like a zugzwang} b5?! 10. Nxb5! cxb5
+11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 (13. Bxd7+ Rxd7) Rxd7 14. Rd1 Qe6
+15. Bxd7+ Nxd7 16. Qb8+ ; the queen sacrifice
+Nxb8 17.Rd8# 1-0
+"#;
+        let board = Board::from_pgn(pgn).unwrap();
+
+        assert_eq!(33, board.move_list.len());
+        assert_eq!(
+            "1n1Rkb1r/p4ppp/4q3/4p1B1/4P3/8/PPP2PPP/2K5 b k - 1 17",
+            board.to_fen()
+        );
+        assert_eq!(
+            GameStatus::Checkmate {
+                winner: PieceColor::White
+            },
+            board.game_status()
+        );
+    }
+
+    #[test]
+    fn test_from_pgn_round_trips_to_pgn() {
+        let board = play(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"]);
+        let imported = Board::from_pgn(&board.to_pgn()).unwrap();
+
+        assert_eq!(board.to_fen(), imported.to_fen());
+    }
+
+    #[test]
+    fn test_from_pgn_reads_castling_written_with_zeros() {
+        let pgn = r#"[SetUp "1"]
+[FEN "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"]
+
+1. 0-0 0-0-0 2.Rfe1 2...Kb8 *
+"#;
+        let board = Board::from_pgn(pgn).unwrap();
+
+        assert_eq!("1k1r3r/8/8/8/8/8/8/R3R1K1 w - - 4 3", board.to_fen());
+    }
+
+    #[test]
+    fn test_from_pgn_rejects_bad_movetext() {
+        assert_eq!(
+            PgnError::InvalidMove(SanError::NoMatchingMove),
+            Board::from_pgn("1. e4 e5 2. Ke3").unwrap_err()
+        );
+        assert_eq!(
+            PgnError::UnclosedComment,
+            Board::from_pgn("1. e4 {never closed").unwrap_err()
+        );
+        assert_eq!(
+            PgnError::UnclosedVariation,
+            Board::from_pgn("1. e4 (1. d4 d5").unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use super::Board;
+use crate::game::piece::{piece_info::PieceColor, Piece};
+
+// What a key stands for, so keys for different parts of the position never coincide
+const PIECE_KEY: u64 = 0;
+const BLACK_TO_MOVE_KEY: u64 = 1;
+const CASTLING_KEY: u64 = 2;
+const EN_PASSANT_KEY: u64 = 3;
+
+// Boards may be any size, so rather than a fixed table of random numbers each key is
+// derived from what it stands for with the splitmix64 mixer
+fn key(kind: u64, value: u64) -> u64 {
+    let mut z = ((value << 2) | kind).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// The key for a piece on the square at `index` in the board's square list
+pub(super) fn piece_key(index: usize, piece: Piece) -> u64 {
+    let value = (index as u64 * 6 + piece.piece_type as u64) * 2 + piece.color as u64;
+    key(PIECE_KEY, value)
+}
+
+impl Board {
+    /// A Zobrist hash of the position, covering the same things as `position_key`: the
+    /// pieces, the side to move, the castling rights and the en passant target.
+    ///
+    /// It is kept up to date move by move rather than recomputed, so it costs nothing to
+    /// read. Writing the board's public fields directly, rather than through `set_piece`
+    /// and the `with_` builders, leaves it stale. Two different positions may in principle
+    /// share a hash, though it is unlikely.
+    pub fn position_hash(&self) -> u64 {
+        self.hash
+    }
+
+    // Hashes the position from scratch, for boards that were just built or loaded
+    pub(super) fn compute_hash(&self) -> u64 {
+        self.board
+            .iter()
+            .enumerate()
+            .filter_map(|(index, square)| square.map(|piece| piece_key(index, piece)))
+            .fold(self.state_hash(), |hash, piece| hash ^ piece)
+    }
+
+    // The part of the hash for everything but the pieces. A move takes it out of the hash
+    // before changing any of it, and puts the new one in afterwards.
+    pub(super) fn state_hash(&self) -> u64 {
+        let mut hash = 0;
+        if self.current_turn == PieceColor::Black {
+            hash ^= key(BLACK_TO_MOVE_KEY, 0);
+        }
+        for (side, color, kingside) in [
+            (0, PieceColor::White, true),
+            (1, PieceColor::White, false),
+            (2, PieceColor::Black, true),
+            (3, PieceColor::Black, false),
+        ] {
+            if let Some(file) = self.castling_rook_file(color, kingside) {
+                hash ^= key(CASTLING_KEY, side * 256 + u64::from(file));
+            }
+        }
+        if let Some(target) = self.en_passant_target {
+            hash ^= key(
+                EN_PASSANT_KEY,
+                u64::from(target.rank) * 256 + u64::from(target.file),
+            );
+        }
+        hash
+    }
+}
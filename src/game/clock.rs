@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+/// One player's side of a chess clock: the time they have left, and the time they gain
+/// back after each of their moves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clock {
+    remaining: Duration,
+    increment: Duration,
+}
+
+impl Clock {
+    pub fn new(initial: Duration, increment: Duration) -> Clock {
+        Clock {
+            remaining: initial,
+            increment,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    /// Whether this player has run out of time.
+    pub fn is_flagged(&self) -> bool {
+        self.remaining.is_zero()
+    }
+
+    // Charges a move that took `elapsed`, only adding the increment if the player still had
+    // time left when they made it
+    pub(crate) fn spend(&mut self, elapsed: Duration) {
+        self.remaining = self.remaining.saturating_sub(elapsed);
+        if !self.is_flagged() {
+            self.remaining += self.increment;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spend_adds_increment() {
+        let mut clock = Clock::new(Duration::from_secs(60), Duration::from_secs(2));
+        clock.spend(Duration::from_secs(10));
+
+        assert_eq!(Duration::from_secs(52), clock.remaining());
+        assert!(!clock.is_flagged());
+    }
+
+    #[test]
+    fn test_running_out_flags_without_increment() {
+        let mut clock = Clock::new(Duration::from_secs(5), Duration::from_secs(2));
+        clock.spend(Duration::from_secs(7));
+
+        assert_eq!(Duration::ZERO, clock.remaining());
+        assert!(clock.is_flagged());
+    }
+}
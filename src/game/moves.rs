@@ -1,17 +1,36 @@
-use crate::game::board::{self, Board};
-use crate::game::piece::{piece_info::PieceLoc, Piece};
+use crate::game::board::Board;
+use crate::game::piece::{
+    piece_info::{PieceColor, PieceLoc, PieceType},
+    Piece,
+};
 
 use self::move_checker::{MoveError, MoveType};
 
 pub mod move_checker;
+pub mod san;
+pub mod uci;
 
-#[derive(Clone, Debug)]
+// The piece types a pawn may become upon reaching the last rank
+pub const PROMOTION_PIECE_TYPES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
     pub piece: Piece,
     pub start_pos: PieceLoc,
     pub end_pos: PieceLoc,
     pub move_type: MoveType,
     pub capturing: bool,
+    pub promotion: Option<PieceType>,
+    // The piece taken by this move and the square it was taken from, which differs from
+    // `end_pos` for en passant
+    pub captured: Option<Piece>,
+    pub captured_pos: Option<PieceLoc>,
 }
 
 impl Move {
@@ -23,26 +42,421 @@ impl Move {
         start: &PieceLoc,
         dest: &PieceLoc,
     ) -> Result<Move, MoveError> {
-        let move_result = move_checker::is_valid_move(board, piece, start, dest);
-        match move_result {
-            Ok(result) => Ok(Move {
-                piece: piece.clone(),
-                start_pos: start.clone(),
-                end_pos: dest.clone(),
-                move_type: result.move_type.clone(),
-                capturing: result.capturing,
-            }),
-            Err(e) => Err(e),
+        Move::new_with_promotion(board, piece, start, dest, None)
+    }
+
+    // Creates a move which may promote a pawn reaching the last rank. Promoting moves must
+    // name the piece type to promote to, and every other move must not.
+    pub fn new_with_promotion(
+        board: &Board,
+        piece: &Piece,
+        start: &PieceLoc,
+        dest: &PieceLoc,
+        promotion: Option<PieceType>,
+    ) -> Result<Move, MoveError> {
+        let new_move = Move::new_pseudo_legal(board, piece, start, dest, promotion)?;
+        Move::checked_for_king_safety(&mut board.clone(), new_move)
+    }
+
+    // Checks the move against how its piece moves, but not yet whether it exposes the
+    // mover's own king, which makes it what engines call pseudo-legal
+    fn new_pseudo_legal(
+        board: &Board,
+        piece: &Piece,
+        start: &PieceLoc,
+        dest: &PieceLoc,
+        promotion: Option<PieceType>,
+    ) -> Result<Move, MoveError> {
+        let result = move_checker::is_valid_move(board, piece, start, dest)?;
+        move_checker::is_valid_promotion(board, piece, dest, promotion)?;
+
+        board.with_captured_piece(Move {
+            piece: *piece,
+            start_pos: *start,
+            end_pos: *dest,
+            move_type: result.move_type,
+            capturing: result.capturing,
+            promotion,
+            captured: None,
+            captured_pos: None,
+        })
+    }
+
+    // Plays the move out and takes it back again on `board` to make sure it doesn't expose
+    // the mover's own king, leaving the board as it was
+    fn checked_for_king_safety(board: &mut Board, new_move: Move) -> Result<Move, MoveError> {
+        let undo = board.make_move(new_move.clone());
+        let in_check = board.is_in_check(new_move.piece.color);
+        board.unmake_move(undo);
+
+        if in_check {
+            Err(MoveError::KingLeftInCheck)
+        } else {
+            Ok(new_move)
         }
     }
 
+    // The piece letter and destination square, i.e. "Nf3", with nothing before a pawn's
+    // square. The file is written as the letter and the rank as the number, as in SAN.
     pub fn get_move_display(&self) -> String {
-        format!(
-            "{}{}{}",
-            board::board_display::get_piece_display(&self.piece, true),
-            board::board_display::convert_rank_numeric_to_alpha(self.end_pos.file)
-                .expect("Somehow converted a file > 7"),
-            self.end_pos.rank + 1
-        )
+        match self.piece.piece_type {
+            PieceType::Pawn => self.end_pos.to_string(),
+            piece_type => format!("{}{}", piece_type.to_char(), self.end_pos),
+        }
+    }
+}
+
+/// Generates every legal move for the side to move.
+///
+/// Each candidate is validated through `Move::new`, so moves that would leave the mover's
+/// own king in check are never included.
+pub fn generate_legal_moves(board: &Board) -> Vec<Move> {
+    let color = board.side_to_move();
+    match (board.checkers(color).as_slice(), board.find_king(color)) {
+        ([], _) | (_, None) => generate_moves_to(board, |_, _| true),
+        // Besides moving the king, a single check can only be answered by capturing the
+        // checker or blocking it, and en passant may capture a checking pawn
+        ([checker], Some(king)) => {
+            let mut answers = squares_between(king, *checker);
+            answers.push(*checker);
+            generate_moves_to(board, |piece, dest| {
+                piece.piece_type == PieceType::King
+                    || answers.contains(&dest)
+                    || (piece.piece_type == PieceType::Pawn
+                        && board.en_passant_target == Some(dest))
+            })
+        }
+        // In double check only the king can move
+        _ => generate_moves_to(board, |piece, _| piece.piece_type == PieceType::King),
+    }
+}
+
+// The squares strictly between two squares sharing a rank, file or diagonal, or none for
+// squares that don't, like a knight and the king it checks
+fn squares_between(from: PieceLoc, to: PieceLoc) -> Vec<PieceLoc> {
+    let rank_diff = i16::from(to.rank) - i16::from(from.rank);
+    let file_diff = i16::from(to.file) - i16::from(from.file);
+    if rank_diff != 0 && file_diff != 0 && rank_diff.abs() != file_diff.abs() {
+        return Vec::new();
+    }
+
+    (1..rank_diff.abs().max(file_diff.abs()))
+        .map(|i| {
+            PieceLoc::new(
+                (i16::from(from.rank) + rank_diff.signum() * i) as u8,
+                (i16::from(from.file) + file_diff.signum() * i) as u8,
+            )
+        })
+        .collect()
+}
+
+/// Generates only the legal captures and promotions for the side to move, the moves that
+/// can swing material immediately.
+pub fn generate_tactical_moves(board: &Board) -> Vec<Move> {
+    let last_rank = match board.side_to_move() {
+        PieceColor::White => board.ranks - 1,
+        PieceColor::Black => 0,
+    };
+
+    // Only squares holding an enemy piece, plus the pawn-only en passant and promotion
+    // squares, need to be tried
+    generate_moves_to(board, |piece, dest| {
+        let is_pawn = piece.piece_type == PieceType::Pawn;
+        board
+            .get_piece_at_location(dest)
+            .is_some_and(|target| target.color != piece.color)
+            || (is_pawn && (board.en_passant_target == Some(dest) || dest.rank == last_rank))
+    })
+    .into_iter()
+    .filter(|m| m.capturing || m.promotion.is_some())
+    .collect()
+}
+
+// Tries every piece of the side to move against each destination the filter accepts. Each
+// candidate is played out on one shared copy of the board to check it for king safety.
+fn generate_moves_to(board: &Board, is_candidate: impl Fn(&Piece, PieceLoc) -> bool) -> Vec<Move> {
+    let mut legal_moves = Vec::new();
+    let mut scratch = board.clone();
+
+    for (start, piece) in board.pieces_of_color(board.side_to_move()) {
+        for dest_rank in 0..board.ranks {
+            for dest_file in 0..board.files {
+                let dest = PieceLoc::new(dest_rank, dest_file);
+                if !is_candidate(&piece, dest) {
+                    continue;
+                }
+                let mut push_if_safe = |new_move| {
+                    if let Ok(new_move) = Move::checked_for_king_safety(&mut scratch, new_move) {
+                        legal_moves.push(new_move);
+                    }
+                };
+                match Move::new_pseudo_legal(board, &piece, &start, &dest, None) {
+                    Ok(new_move) => push_if_safe(new_move),
+                    // A pawn reaching the last rank may become any of the promotion pieces
+                    Err(MoveError::PromotionRequired) => {
+                        for promotion in PROMOTION_PIECE_TYPES {
+                            if let Ok(new_move) = Move::new_pseudo_legal(
+                                board,
+                                &piece,
+                                &start,
+                                &dest,
+                                Some(promotion),
+                            ) {
+                                push_if_safe(new_move);
+                            }
+                        }
+                    }
+                    Err(_) => (),
+                }
+            }
+        }
+    }
+    legal_moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::piece_info::PieceColor;
+
+    #[test]
+    fn test_pinned_bishop_cannot_leave_pin() {
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // E1
+            .with_piece(
+                PieceLoc::new(1, 3),
+                Piece::new(PieceType::Bishop, PieceColor::White),
+            ) // D2
+            .with_piece(
+                PieceLoc::new(3, 1),
+                Piece::new(PieceType::Bishop, PieceColor::Black),
+            ) // B4
+            .with_piece(
+                PieceLoc::new(7, 4),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ); // E8
+
+        let bishop = board.board[11].unwrap();
+        let start = PieceLoc::new(1, 3);
+
+        assert_eq!(
+            MoveError::KingLeftInCheck,
+            Move::new(&board, &bishop, &start, &PieceLoc::new(2, 4)).unwrap_err()
+        );
+        // Sliding along the pin, or capturing the pinning piece, is still allowed
+        assert!(Move::new(&board, &bishop, &start, &PieceLoc::new(2, 2)).is_ok());
+        assert!(Move::new(&board, &bishop, &start, &PieceLoc::new(3, 1)).is_ok());
+    }
+
+    #[test]
+    fn test_king_cannot_walk_into_attacked_square() {
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // E1
+            .with_piece(
+                PieceLoc::new(1, 0),
+                Piece::new(PieceType::Rook, PieceColor::Black),
+            ) // A2
+            .with_piece(
+                PieceLoc::new(7, 4),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ); // E8
+
+        let king = board.board[4].unwrap();
+        let start = PieceLoc::new(0, 4);
+
+        assert_eq!(
+            MoveError::KingLeftInCheck,
+            Move::new(&board, &king, &start, &PieceLoc::new(1, 4)).unwrap_err()
+        );
+        assert!(Move::new(&board, &king, &start, &PieceLoc::new(0, 5)).is_ok());
+    }
+
+    #[test]
+    fn test_king_cannot_move_next_to_enemy_king() {
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(3, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // E4
+            .with_piece(
+                PieceLoc::new(5, 4),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ); // E6
+
+        // The black king attacks the squares around it, like any other piece would
+        assert!(board.is_square_attacked(PieceLoc::new(4, 4), PieceColor::Black));
+        assert!(!board.is_square_attacked(PieceLoc::new(3, 4), PieceColor::Black));
+
+        let king = board.board[28].unwrap();
+        let start = PieceLoc::new(3, 4);
+        for file in 3..=5 {
+            assert_eq!(
+                MoveError::KingLeftInCheck,
+                Move::new(&board, &king, &start, &PieceLoc::new(4, file)).unwrap_err()
+            );
+        }
+        assert!(Move::new(&board, &king, &start, &PieceLoc::new(3, 3)).is_ok());
+        assert!(board
+            .legal_moves_for(start)
+            .iter()
+            .all(|m| m.end_pos.rank < 4));
+    }
+
+    #[test]
+    fn test_move_display_writes_file_then_rank() {
+        let board = Board::new();
+        let pawn_move = Move::from_san(&board, "e4").unwrap();
+        assert_eq!(PieceLoc::new(3, 4), pawn_move.end_pos);
+        assert_eq!("e4", pawn_move.get_move_display());
+
+        let knight_move = Move::from_san(&board, "Nf3").unwrap();
+        assert_eq!("Nf3", knight_move.get_move_display());
+    }
+
+    fn setup_promotion() -> Board {
+        let pawn = Piece {
+            has_moved: true,
+            ..Piece::new(PieceType::Pawn, PieceColor::White)
+        };
+        Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // A1
+            .with_piece(PieceLoc::new(6, 2), pawn) // C7
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ) // H8
+    }
+
+    #[test]
+    fn test_pawn_reaching_last_rank_must_promote() {
+        let board = setup_promotion();
+        let pawn = board.board[50].unwrap();
+        let start = PieceLoc::new(6, 2);
+        let dest = PieceLoc::new(7, 2);
+
+        assert_eq!(
+            MoveError::PromotionRequired,
+            Move::new(&board, &pawn, &start, &dest).unwrap_err()
+        );
+        assert_eq!(
+            MoveError::CannotPromote,
+            Move::new_with_promotion(&board, &pawn, &start, &dest, Some(PieceType::King))
+                .unwrap_err()
+        );
+
+        let promotion =
+            Move::new_with_promotion(&board, &pawn, &start, &dest, Some(PieceType::Knight))
+                .unwrap();
+        let board = board.move_piece(promotion).unwrap();
+        let promoted = board.board[58].unwrap();
+        assert_eq!(PieceType::Knight, promoted.piece_type);
+        assert_eq!(PieceColor::White, promoted.color);
+        assert!(board.board[50].is_none());
+    }
+
+    #[test]
+    fn test_cannot_promote_before_last_rank() {
+        let board = Board::new();
+        let pawn = board.board[12].unwrap(); // E2
+
+        assert_eq!(
+            MoveError::CannotPromote,
+            Move::new_with_promotion(
+                &board,
+                &pawn,
+                &PieceLoc::new(1, 4),
+                &PieceLoc::new(3, 4),
+                Some(PieceType::Queen)
+            )
+            .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_pawn_capture_onto_last_rank_generates_each_promotion() {
+        let pawn = Piece {
+            has_moved: true,
+            ..Piece::new(PieceType::Pawn, PieceColor::White)
+        };
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // A1
+            .with_piece(PieceLoc::new(6, 6), pawn) // G7
+            .with_piece(
+                PieceLoc::new(7, 0),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ) // A8
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::Knight, PieceColor::Black),
+            ); // H8
+
+        let capture_promotions: Vec<Move> = generate_legal_moves(&board)
+            .into_iter()
+            .filter(|m| m.start_pos == PieceLoc::new(6, 6) && m.end_pos == PieceLoc::new(7, 7))
+            .collect();
+
+        assert_eq!(4, capture_promotions.len());
+        assert!(capture_promotions.iter().all(|m| m.capturing));
+        for piece_type in PROMOTION_PIECE_TYPES {
+            assert!(capture_promotions
+                .iter()
+                .any(|m| m.promotion == Some(piece_type)));
+        }
+
+        // Capturing with promotion leaves the promoted piece on H8 and the knight in the graveyard
+        let queen_promotion = capture_promotions
+            .into_iter()
+            .find(|m| m.promotion == Some(PieceType::Queen))
+            .unwrap();
+        let board = board.move_piece(queen_promotion).unwrap();
+        let promoted = board.board[63].unwrap();
+        assert_eq!(PieceType::Queen, promoted.piece_type);
+        assert_eq!(PieceColor::White, promoted.color);
+        assert_eq!(
+            1,
+            board.captured_count(PieceColor::Black, PieceType::Knight)
+        );
+    }
+
+    #[test]
+    fn test_tactical_moves_are_captures_and_promotions() {
+        assert!(generate_tactical_moves(&Board::new()).is_empty());
+
+        let pawn = Piece {
+            has_moved: true,
+            ..Piece::new(PieceType::Pawn, PieceColor::White)
+        };
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // A1
+            .with_piece(PieceLoc::new(6, 6), pawn) // G7
+            .with_piece(
+                PieceLoc::new(7, 0),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ) // A8
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::Knight, PieceColor::Black),
+            ); // H8
+
+        // Each promotion on g8 and on h8, but none of the quiet king moves
+        let tactical_moves = generate_tactical_moves(&board);
+        assert_eq!(8, tactical_moves.len());
+        assert!(tactical_moves.iter().all(|m| m.promotion.is_some()));
+        assert_eq!(4, tactical_moves.iter().filter(|m| m.capturing).count());
     }
 }
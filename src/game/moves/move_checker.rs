@@ -4,11 +4,14 @@ use crate::game::piece::{
     Piece,
 };
 use core::fmt;
+use log::trace;
 
-use super::Move;
+use super::PROMOTION_PIECE_TYPES;
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MoveType {
+    #[default]
     Normal,
     EnPassant,
     Castling,
@@ -25,12 +28,13 @@ pub enum MoveError {
     WrongColorPiece,
     RankDifferenceGreater,
     FileDifferenceGreater,
-    MoveOutOfBounds,
+    MoveOutOfBounds { dest: PieceLoc },
     MoveNotStraightLine,
     NoPositionChange,
-    OccupiedBySameColor,
+    OccupiedBySameColor { at: PieceLoc },
     PawnMustMoveForward,
     PawnMustCaptureDiagonal,
+    PawnDiagonalWithoutCapture,
     PawnEnPassantNotValid,
     KnightInvalidMove,
     RookMustMoveCardinal,
@@ -39,34 +43,103 @@ pub enum MoveError {
     CannotCastleWithMovedRook,
     CannotCastleWithMovedKing,
     CannotCastleThroughPiece,
+    CannotCastleOutOfCheck,
+    CannotCastleThroughCheck,
+    PathObstructed { at: PieceLoc },
+    KingLeftInCheck,
+    PieceNotAtStart { start: PieceLoc },
+    NoCastlingRights,
+    PromotionRequired,
+    CannotPromote,
+    GameOver,
+    NoDrawOffer,
+    NoDrawToClaim,
+    NoPieceToCapture { at: PieceLoc },
 }
 
 impl fmt::Display for MoveError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let output;
-        match *self {
-                    MoveError::WrongColorPiece => output = "It is not your turn to move.",
-                    MoveError::RankDifferenceGreater => output = "Piece attempted to move too many ranks at once.",
-                    MoveError::FileDifferenceGreater => output = "Piece attempted to move too many files at once.",
-                    MoveError::MoveOutOfBounds => output = "Piece attempted to move out of bounds.",
-                    MoveError::MoveNotStraightLine => output = "Piece attempted to move to an invalid square.",
-                    MoveError::NoPositionChange => output = "A piece cannot be moved to the square it already occupies.",
-                    MoveError::OccupiedBySameColor => output = "A piece cannot be moved to a square that is occupied by a piece of the same color.",
-                    MoveError::PawnMustMoveForward => output = "Pawns can only move forward.",
-                    MoveError::PawnMustCaptureDiagonal => output = "Pawns cannot capture pieces directly in front of them.",
-                    MoveError::PawnEnPassantNotValid => output = "Conditions not met to perform en passant",
-                    MoveError::KnightInvalidMove => output = "Knights may only move two squares in one cardinal direction, and one square in a perpendicular direction.",
-                    MoveError::RookMustMoveCardinal => output = "Rooks may only move horizontally or vertically.",
-                    MoveError::BishopMustMoveDiagonal => output = "Bishops may only move diagonally.",
-                    MoveError::NoRookToCastleWith => output = "There is no valid rook to castle with on that side.",
-                    MoveError::CannotCastleWithMovedRook => output = "You cannot castle with a rook that has previously moved.",
-                    MoveError::CannotCastleWithMovedKing => output = "You cannot castle with a king that has previously moved.",
-                    MoveError::CannotCastleThroughPiece => output = "You cannot castle with a piece between the king and rook.",
-                }
-        write!(f, "Invalid Move: {}", output)
+        write!(f, "Invalid Move: ")?;
+        let output = match *self {
+            MoveError::WrongColorPiece => "It is not your turn to move.",
+            MoveError::RankDifferenceGreater => "Piece attempted to move too many ranks at once.",
+            MoveError::FileDifferenceGreater => "Piece attempted to move too many files at once.",
+            // The square may be off the end of the alphabet, so it is given by number
+            MoveError::MoveOutOfBounds { dest } => {
+                return write!(
+                    f,
+                    "Piece attempted to move out of bounds, to rank {}, file {}.",
+                    u16::from(dest.rank) + 1,
+                    u16::from(dest.file) + 1
+                );
+            }
+            MoveError::MoveNotStraightLine => "Piece attempted to move to an invalid square.",
+            MoveError::NoPositionChange => {
+                "A piece cannot be moved to the square it already occupies."
+            }
+            MoveError::OccupiedBySameColor { at } => {
+                return write!(
+                    f,
+                    "A piece cannot be moved to {}, which is occupied by a piece of the same color.",
+                    at
+                );
+            }
+            MoveError::PawnMustMoveForward => "Pawns can only move forward.",
+            MoveError::PawnMustCaptureDiagonal => {
+                "Pawns cannot capture pieces directly in front of them."
+            }
+            MoveError::PawnDiagonalWithoutCapture => {
+                "Pawns can only move diagonally when capturing."
+            }
+            MoveError::PawnEnPassantNotValid => "Conditions not met to perform en passant",
+            MoveError::KnightInvalidMove => {
+                "Knights may only move two squares in one cardinal direction, and one square in a perpendicular direction."
+            }
+            MoveError::RookMustMoveCardinal => "Rooks may only move horizontally or vertically.",
+            MoveError::BishopMustMoveDiagonal => "Bishops may only move diagonally.",
+            MoveError::NoRookToCastleWith => "There is no valid rook to castle with on that side.",
+            MoveError::CannotCastleWithMovedRook => {
+                "You cannot castle with a rook that has previously moved."
+            }
+            MoveError::CannotCastleWithMovedKing => {
+                "You cannot castle with a king that has previously moved."
+            }
+            MoveError::CannotCastleThroughPiece => {
+                "You cannot castle with a piece between the king and rook."
+            }
+            MoveError::CannotCastleOutOfCheck => "You cannot castle while your king is in check.",
+            MoveError::CannotCastleThroughCheck => {
+                "You cannot castle through a square that is under attack."
+            }
+            MoveError::PathObstructed { at } => {
+                return write!(f, "Piece attempted to move through another piece on {}.", at);
+            }
+            MoveError::KingLeftInCheck => "You cannot make a move that leaves your king in check.",
+            MoveError::PieceNotAtStart { start } => {
+                return write!(f, "That piece is not on {}, the square it is moving from.", start);
+            }
+            MoveError::NoCastlingRights => {
+                "You have given up the right to castle on that side by moving the king or rook."
+            }
+            MoveError::PromotionRequired => {
+                "A pawn reaching the last rank must be promoted to a queen, rook, bishop, or knight."
+            }
+            MoveError::CannotPromote => {
+                "Only a pawn reaching the last rank can be promoted, to a queen, rook, bishop, or knight."
+            }
+            MoveError::GameOver => "The game is already over.",
+            MoveError::NoDrawOffer => "Your opponent has not offered a draw.",
+            MoveError::NoDrawToClaim => "There is no draw to claim in this position.",
+            MoveError::NoPieceToCapture { at } => {
+                return write!(f, "There is no piece on {} to capture.", at);
+            }
+        };
+        f.write_str(output)
     }
 }
 
+impl std::error::Error for MoveError {}
+
 fn is_diagonal_move(start: &PieceLoc, dest: &PieceLoc) -> bool {
     dest.file.abs_diff(start.file) == dest.rank.abs_diff(start.rank)
 }
@@ -80,42 +153,99 @@ fn is_knight_move(start: &PieceLoc, dest: &PieceLoc) -> bool {
         || (dest.file.abs_diff(start.file) == 1 && dest.rank.abs_diff(start.rank) == 2)
 }
 
+// Walks every square strictly between start and dest along a rank, file, or diagonal,
+// returning false as soon as one of them is occupied.
+fn is_path_clear(board: &Board, start: &PieceLoc, dest: &PieceLoc) -> bool {
+    first_obstruction(board, start, dest).is_none()
+}
+
+// The first occupied square strictly between `start` and `dest`, walking from `start`
+fn first_obstruction(board: &Board, start: &PieceLoc, dest: &PieceLoc) -> Option<PieceLoc> {
+    let rank_step = (dest.rank as i8 - start.rank as i8).signum();
+    let file_step = (dest.file as i8 - start.file as i8).signum();
+
+    let mut rank = start.rank as i8 + rank_step;
+    let mut file = start.file as i8 + file_step;
+    while (rank, file) != (dest.rank as i8, dest.file as i8) {
+        let loc = PieceLoc::new(rank as u8, file as u8);
+        if board.get_piece_at_location(loc).is_some() {
+            return Some(loc);
+        }
+        rank += rank_step;
+        file += file_step;
+    }
+    None
+}
+
+/// Checks whether the piece standing on `start` attacks `target`, meaning it could capture
+/// an enemy piece there.
+///
+/// Unlike `is_valid_move` this ignores whose turn it is and what occupies the target, and
+/// never counts pawn pushes or castling as attacks.
+pub fn attacks_square(board: &Board, piece: &Piece, start: &PieceLoc, target: &PieceLoc) -> bool {
+    if start == target {
+        return false;
+    }
+
+    match piece.piece_type {
+        PieceType::Pawn => {
+            let attacked_rank = match piece.color {
+                PieceColor::White => start.rank as i8 + 1,
+                PieceColor::Black => start.rank as i8 - 1,
+            };
+            target.rank as i8 == attacked_rank && target.file.abs_diff(start.file) == 1
+        }
+        PieceType::Knight => is_knight_move(start, target),
+        PieceType::King => {
+            target.rank.abs_diff(start.rank) <= 1 && target.file.abs_diff(start.file) <= 1
+        }
+        PieceType::Rook => is_cardinal_move(start, target) && is_path_clear(board, start, target),
+        PieceType::Bishop => is_diagonal_move(start, target) && is_path_clear(board, start, target),
+        PieceType::Queen => {
+            (is_diagonal_move(start, target) || is_cardinal_move(start, target))
+                && is_path_clear(board, start, target)
+        }
+    }
+}
+
+/// The square of the pawn taken by an en passant capture onto `target`: the pawn that
+/// skipped over the target, one rank past it from the capturer's side. The capturing pawn
+/// always starts on this same rank.
+pub(crate) fn en_passant_victim(target: PieceLoc, capturer: PieceColor) -> PieceLoc {
+    match capturer {
+        PieceColor::White => PieceLoc::new(target.rank - 1, target.file),
+        PieceColor::Black => PieceLoc::new(target.rank + 1, target.file),
+    }
+}
+
 fn can_en_passant(
     board: &Board,
     piece: &Piece,
     start: &PieceLoc,
     dest: &PieceLoc,
 ) -> Result<MoveResult, MoveError> {
-    // Special case: check for en passant conditions
-    let (required_start_rank, valid_destination_rank) = match piece.color {
-        PieceColor::White => (4, 5),
-        PieceColor::Black => (2, 1),
+    // Special case: check for en passant conditions, which can only arise beside a pawn
+    // that has just made its two-square move from its starting rank
+    let valid_destination_rank = match piece.color {
+        PieceColor::White => board.ranks - 3,
+        PieceColor::Black => 2,
     };
 
-    println!("{:?}", required_start_rank);
-
-    // If moving pawn is in correct spot
-    if start.rank == required_start_rank {
-        println!("In correct spot");
-        if let Some(last_move) = board.get_previous_move() {
-            // Previous move was a pawn move
-            if last_move.piece.piece_type == PieceType::Pawn {
-                println!("Last move was pawn");
-                // Previous pawn move was a two-square move
-                if last_move.start_pos.rank.abs_diff(last_move.end_pos.rank) == 2 {
-                    // Attempting a capture to the square behind the white pawn that moved two
-                    println!("Last move was 2 spaces");
-                    if dest.rank == valid_destination_rank && dest.file == last_move.end_pos.file {
-                        return Ok(MoveResult {
-                            move_type: MoveType::EnPassant,
-                            capturing: true,
-                        });
-                    }
-                }
-            }
+    // The previous move was a two-square pawn move, which skipped over the target square
+    if let Some(target) = board.en_passant_target {
+        trace!("En passant target is {}", target);
+        let victim = en_passant_victim(target, piece.color);
+
+        // Attempting a capture to the square behind the pawn that moved two, from beside it
+        if start.rank == victim.rank && dest.rank == valid_destination_rank && *dest == target {
+            trace!("Pawn on {} is in position for en passant", start);
+            return Ok(MoveResult {
+                move_type: MoveType::EnPassant,
+                capturing: true,
+            });
         }
     }
-    return Err(MoveError::PawnEnPassantNotValid);
+    Err(MoveError::PawnEnPassantNotValid)
 }
 
 // fn valid_destinations(board: &Board, piece: &Piece, current_loc: &PieceLoc) -> Vec<PieceLoc> {
@@ -148,6 +278,48 @@ fn can_en_passant(
 //     valid_dests;
 // }
 
+/// Checks that a move promotes exactly when it should: a pawn arriving on the last rank
+/// must name one of the promotion piece types, and no other move may name one at all.
+pub fn is_valid_promotion(
+    board: &Board,
+    piece: &Piece,
+    dest: &PieceLoc,
+    promotion: Option<PieceType>,
+) -> Result<(), MoveError> {
+    let last_rank = match piece.color {
+        PieceColor::White => board.ranks - 1,
+        PieceColor::Black => 0,
+    };
+    let promoting = piece.piece_type == PieceType::Pawn && dest.rank == last_rank;
+
+    match promotion {
+        None if promoting => Err(MoveError::PromotionRequired),
+        None => Ok(()),
+        Some(piece_type) if promoting && PROMOTION_PIECE_TYPES.contains(&piece_type) => Ok(()),
+        Some(_) => Err(MoveError::CannotPromote),
+    }
+}
+
+// Castling is written as the king moving to its destination when that is at least two files
+// away, as in standard chess, and otherwise as the king moving onto its own castling rook,
+// as Chess960 castling usually is. Each castling move has exactly one of the two forms.
+fn is_castling_attempt(board: &Board, piece: &Piece, start: &PieceLoc, dest: &PieceLoc) -> bool {
+    if piece.piece_type != PieceType::King
+        || dest.rank != start.rank
+        || start.rank != board.back_rank(piece.color)
+    {
+        return false;
+    }
+
+    let kingside = dest.file > start.file;
+    let (king_to, _) = board.castling_destinations(start.rank, kingside);
+    if king_to.file.abs_diff(start.file) >= 2 {
+        dest.file == king_to.file
+    } else {
+        board.castling_rook_file(piece.color, kingside) == Some(dest.file)
+    }
+}
+
 /// Handles checking every type of piece to confirm that a proposed move is valid.
 ///
 /// If the move is valid, it will return Ok(bool), where the bool indicates whether
@@ -162,25 +334,29 @@ pub fn is_valid_move(
     dest: &PieceLoc,
 ) -> Result<MoveResult, MoveError> {
     // Confirm the correct color piece is being moved depending on whose turn it is
-    if board.current_turn != piece.color {
+    if board.side_to_move() != piece.color {
         return Err(MoveError::WrongColorPiece);
     }
 
     // Confirm the player made a move within the board's limits, and that
     // it could theoretically move a piece from it's starting square.
     if dest.file >= board.files || dest.rank >= board.ranks {
-        return Err(MoveError::MoveOutOfBounds);
+        return Err(MoveError::MoveOutOfBounds { dest: *dest });
     }
     if dest.rank == start.rank && dest.file == start.file {
         return Err(MoveError::NoPositionChange);
     }
 
+    // Castling never captures, and reports any piece in its way as an obstruction rather
+    // than an occupied destination
+    let castling = is_castling_attempt(board, piece, start, dest);
+
     // Check if there is a piece at the target destination, making this a capturing move
     let mut capturing = false;
     let mut move_type = MoveType::Normal;
-    if let Some(existing_piece) = board.get_piece_at_location(*dest) {
+    if let (Some(existing_piece), false) = (board.get_piece_at_location(*dest), castling) {
         if existing_piece.color == piece.color {
-            return Err(MoveError::OccupiedBySameColor);
+            return Err(MoveError::OccupiedBySameColor { at: *dest });
         } else {
             capturing = true;
         }
@@ -208,61 +384,105 @@ pub fn is_valid_move(
                 return Err(MoveError::PawnMustMoveForward);
             }
 
-            // Special case: check for en passant conditions
-            let en_passant_result = can_en_passant(board, piece, start, dest);
-            if en_passant_result.is_ok() {
-                capturing = true;
-                move_type = MoveType::EnPassant;
-            }
+            let diagonal_step =
+                dest.file.abs_diff(start.file) == 1 && dest.rank.abs_diff(start.rank) == 1;
 
-            if capturing {
-                // Pawns can only capture diagonally adjacent pieces
-                if dest.file.abs_diff(start.file) != 1 || dest.rank.abs_diff(start.rank) != 1 {
+            if start.file == dest.file {
+                // Pawns can't capture the piece directly in front of them
+                if capturing {
                     return Err(MoveError::PawnMustCaptureDiagonal);
                 }
-            } else {
-                if start.file != dest.file {
-                    return Err(MoveError::PawnMustMoveForward);
+                // A two-square move can't jump over a piece in the way
+                if let Some(at) = first_obstruction(board, start, dest) {
+                    return Err(MoveError::PathObstructed { at });
+                }
+                Ok(MoveResult {
+                    move_type,
+                    capturing,
+                })
+            } else if !diagonal_step {
+                // Pawns can only capture diagonally adjacent pieces
+                if capturing {
+                    Err(MoveError::PawnMustCaptureDiagonal)
+                } else {
+                    Err(MoveError::PawnMustMoveForward)
                 }
+            } else if capturing {
+                Ok(MoveResult {
+                    move_type,
+                    capturing,
+                })
+            } else if board.en_passant_target == Some(*dest) {
+                // Special case: an empty diagonal square is only reachable by en passant
+                can_en_passant(board, piece, start, dest)
+            } else {
+                Err(MoveError::PawnDiagonalWithoutCapture)
             }
-
-            Ok(MoveResult {
-                move_type,
-                capturing,
-            })
         }
         PieceType::King => {
             // SPECIAL MOVE: Castling
-            if dest.rank == start.rank && dest.file.abs_diff(start.file) == 2 {
+            if castling {
                 // King cannot have moved for castling to be valid
                 if piece.has_moved {
                     return Err(MoveError::CannotCastleWithMovedKing);
                 }
 
-                let castling_rook_index;
-                if dest.file < start.file {
-                    // Castling queenside
-                    castling_rook_index = 0;
-                } else {
-                    // Castling kingside
-                    castling_rook_index = 7;
+                // The rook is wherever this side's castling right says it started
+                let kingside = dest.file > start.file;
+                let castling_rook_index = match board.castling_rook_file(piece.color, kingside) {
+                    Some(file) => file,
+                    None => return Err(MoveError::NoCastlingRights),
+                };
+                let rook_loc = PieceLoc::new(start.rank, castling_rook_index);
+
+                match board.get_piece_at_location(rook_loc) {
+                    Some(rook)
+                        if rook.piece_type == PieceType::Rook && rook.color == piece.color =>
+                    {
+                        if rook.has_moved {
+                            return Err(MoveError::CannotCastleWithMovedRook);
+                        }
+                    }
+                    _ => return Err(MoveError::NoRookToCastleWith),
                 }
 
-                if let Some(rook) =
-                    board.get_piece_at_location(PieceLoc::new(dest.rank, castling_rook_index))
-                {
-                    if !rook.has_moved {
-                        move_type = MoveType::Castling;
-                        Ok(MoveResult {
-                            move_type,
-                            capturing,
-                        })
-                    } else {
-                        Err(MoveError::CannotCastleWithMovedRook)
+                // Every square either piece crosses or lands on must be empty, apart from the
+                // king and the rook themselves
+                let (king_to, rook_to) = board.castling_destinations(start.rank, kingside);
+                let files = [start.file, king_to.file, rook_loc.file, rook_to.file];
+                let low_file = *files.iter().min().expect("There are four files");
+                let high_file = *files.iter().max().expect("There are four files");
+                for file in low_file..=high_file {
+                    let loc = PieceLoc::new(start.rank, file);
+                    if loc != *start
+                        && loc != rook_loc
+                        && board.get_piece_at_location(loc).is_some()
+                    {
+                        return Err(MoveError::CannotCastleThroughPiece);
                     }
+                }
+
+                if board.is_square_attacked(*start, piece.color.flip()) {
+                    return Err(MoveError::CannotCastleOutOfCheck);
+                }
+                // Nor may the king cross a square that is attacked, whichever way it goes
+                let crossed = if king_to.file > start.file {
+                    start.file + 1..=king_to.file
                 } else {
-                    Err(MoveError::NoRookToCastleWith)
+                    king_to.file..=start.file.saturating_sub(1)
+                };
+                for file in crossed {
+                    if board.is_square_attacked(PieceLoc::new(start.rank, file), piece.color.flip())
+                    {
+                        return Err(MoveError::CannotCastleThroughCheck);
+                    }
                 }
+
+                move_type = MoveType::Castling;
+                Ok(MoveResult {
+                    move_type,
+                    capturing,
+                })
             } else if dest.file.abs_diff(start.file) > 1 {
                 Err(MoveError::FileDifferenceGreater)
             } else if dest.rank.abs_diff(start.rank) > 1 {
@@ -275,33 +495,39 @@ pub fn is_valid_move(
             }
         }
         PieceType::Rook => {
-            if is_cardinal_move(start, dest) {
+            if !(is_cardinal_move(start, dest)) {
+                Err(MoveError::RookMustMoveCardinal)
+            } else if let Some(at) = first_obstruction(board, start, dest) {
+                Err(MoveError::PathObstructed { at })
+            } else {
                 Ok(MoveResult {
                     move_type,
                     capturing,
                 })
-            } else {
-                Err(MoveError::RookMustMoveCardinal)
             }
         }
         PieceType::Bishop => {
-            if is_diagonal_move(start, dest) {
+            if !(is_diagonal_move(start, dest)) {
+                Err(MoveError::BishopMustMoveDiagonal)
+            } else if let Some(at) = first_obstruction(board, start, dest) {
+                Err(MoveError::PathObstructed { at })
+            } else {
                 Ok(MoveResult {
                     move_type,
                     capturing,
                 })
-            } else {
-                Err(MoveError::BishopMustMoveDiagonal)
             }
         }
         PieceType::Queen => {
-            if is_diagonal_move(start, dest) || is_cardinal_move(start, dest) {
+            if !(is_diagonal_move(start, dest) || is_cardinal_move(start, dest)) {
+                Err(MoveError::MoveNotStraightLine)
+            } else if let Some(at) = first_obstruction(board, start, dest) {
+                Err(MoveError::PathObstructed { at })
+            } else {
                 Ok(MoveResult {
                     move_type,
                     capturing,
                 })
-            } else {
-                Err(MoveError::MoveNotStraightLine)
             }
         }
         PieceType::Knight => {
@@ -320,126 +546,177 @@ pub fn is_valid_move(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::board::Board;
+
+    // Sets up a test position from FEN, so fixtures read as the position they describe
+    fn board_from(fen: &str) -> Board {
+        Board::from_fen(fen).expect("Test FEN should be valid")
+    }
 
     #[test]
-    fn test_minimal_diagonal_move_all_directions_return_true() {
+    fn test_error_messages_name_the_square() {
         assert_eq!(
-            true,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 2))
+            "Invalid Move: Piece attempted to move through another piece on e3.",
+            MoveError::PathObstructed {
+                at: PieceLoc::new(2, 4)
+            }
+            .to_string()
         );
         assert_eq!(
-            true,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 0))
+            "Invalid Move: A piece cannot be moved to d2, which is occupied by a piece of the same color.",
+            MoveError::OccupiedBySameColor {
+                at: PieceLoc::new(1, 3)
+            }
+            .to_string()
         );
         assert_eq!(
-            true,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 2))
+            "Invalid Move: Piece attempted to move out of bounds, to rank 9, file 1.",
+            MoveError::MoveOutOfBounds {
+                dest: PieceLoc::new(8, 0)
+            }
+            .to_string()
         );
         assert_eq!(
-            true,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 0))
+            "Invalid Move: It is not your turn to move.",
+            MoveError::WrongColorPiece.to_string()
         );
     }
 
     #[test]
-    fn test_diagonal_move_all_cardinal_directions_return_false() {
-        assert_eq!(
-            false,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 1))
-        );
-        assert_eq!(
-            false,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(1, 2))
-        );
-        assert_eq!(
-            false,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 1))
-        );
+    fn test_move_error_converts_into_boxed_error() {
+        fn play_e5() -> Result<Board, Box<dyn std::error::Error>> {
+            let board = Board::new();
+            let pawn = board.get_piece_at_location(PieceLoc::new(1, 4)).unwrap();
+            let m = crate::game::moves::Move::new(
+                &board,
+                &pawn,
+                &PieceLoc::new(1, 4),
+                &PieceLoc::new(4, 4),
+            )?;
+            Ok(board.move_piece(m)?)
+        }
+
+        let error = play_e5().unwrap_err();
         assert_eq!(
-            false,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(1, 0))
+            MoveError::RankDifferenceGreater.to_string(),
+            error.to_string()
         );
+        assert!(error.downcast_ref::<MoveError>().is_some());
+    }
+
+    #[test]
+    fn test_minimal_diagonal_move_all_directions_return_true() {
+        assert!(is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 2)));
+        assert!(is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 0)));
+        assert!(is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 2)));
+        assert!(is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 0)));
+    }
+
+    #[test]
+    fn test_diagonal_move_all_cardinal_directions_return_false() {
+        assert!(!is_diagonal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(2, 1)
+        ));
+        assert!(!is_diagonal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(1, 2)
+        ));
+        assert!(!is_diagonal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(0, 1)
+        ));
+        assert!(!is_diagonal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(1, 0)
+        ));
     }
 
     #[test]
     fn test_diagonal_move_bad_returns_false() {
         // Knight move
-        assert_eq!(
-            false,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(3, 2))
-        );
+        assert!(!is_diagonal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(3, 2)
+        ));
         // Completely bad move
-        assert_eq!(
-            false,
-            is_diagonal_move(&PieceLoc::new(1, 1), &PieceLoc::new(3, 7))
-        );
+        assert!(!is_diagonal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(3, 7)
+        ));
     }
 
     #[test]
     fn test_minimal_cardinal_move_all_directions_return_true() {
-        assert_eq!(
-            true,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 1))
-        );
-        assert_eq!(
-            true,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(1, 2))
-        );
-        assert_eq!(
-            true,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 1))
-        );
-        assert_eq!(
-            true,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(1, 0))
-        );
+        assert!(is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 1)));
+        assert!(is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(1, 2)));
+        assert!(is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 1)));
+        assert!(is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(1, 0)));
     }
 
     #[test]
     fn test_cardinal_move_all_diagonal_directions_return_false() {
-        assert_eq!(
-            false,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 2))
-        );
-        assert_eq!(
-            false,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 0))
-        );
-        assert_eq!(
-            false,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(2, 0))
-        );
-        assert_eq!(
-            false,
-            is_cardinal_move(&PieceLoc::new(1, 1), &PieceLoc::new(0, 2))
-        );
+        assert!(!is_cardinal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(2, 2)
+        ));
+        assert!(!is_cardinal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(0, 0)
+        ));
+        assert!(!is_cardinal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(2, 0)
+        ));
+        assert!(!is_cardinal_move(
+            &PieceLoc::new(1, 1),
+            &PieceLoc::new(0, 2)
+        ));
     }
 
     #[cfg(test)]
     mod pawn_tests {
         use crate::game::board::Board;
-        use crate::game::moves::{move_checker::MoveType, Move};
+        use crate::game::moves::{
+            move_checker::{MoveError, MoveType},
+            Move,
+        };
         use crate::game::piece::piece_info::{PieceColor, PieceLoc, PieceType};
+        use crate::game::piece::Piece;
+
+        // A pawn move for `Board::replay`, which checks it and fills in the rest
+        fn pawn_move(color: PieceColor, start: &str, end: &str) -> Move {
+            Move {
+                piece: Piece::new(PieceType::Pawn, color),
+                start_pos: PieceLoc::from_notation(start).unwrap(),
+                end_pos: PieceLoc::from_notation(end).unwrap(),
+                move_type: MoveType::Normal,
+                capturing: false,
+                promotion: None,
+                captured: None,
+                captured_pos: None,
+            }
+        }
 
+        // After 1.e4, but with white to move again so the e-pawn can keep going
         fn setup_e4() -> Board {
-            let mut board = Board::new();
-            board.board[28] = board.board[12];
-            board.board[12] = None;
-            board
+            super::board_from("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1")
         }
 
         fn setup_e4_e5() -> Board {
-            let mut board = setup_e4();
-            board.board[36] = board.board[52];
-            board.board[52] = None;
-            board
+            Board::replay(&[
+                pawn_move(PieceColor::White, "e2", "e4"),
+                pawn_move(PieceColor::Black, "e7", "e5"),
+            ])
+            .unwrap()
         }
 
         fn setup_e4_d5() -> Board {
-            let mut board = setup_e4();
-            board.board[35] = board.board[51];
-            board.board[51] = None;
-            board
+            Board::replay(&[
+                pawn_move(PieceColor::White, "e2", "e4"),
+                pawn_move(PieceColor::Black, "d7", "d5"),
+            ])
+            .unwrap()
         }
 
         #[test]
@@ -453,10 +730,10 @@ mod tests {
             let end_pos = PieceLoc { rank: 2, file: 5 };
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
 
@@ -473,10 +750,10 @@ mod tests {
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
             println!("{:?}", verdict);
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
 
@@ -493,10 +770,10 @@ mod tests {
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
             println!("{:?}", verdict);
 
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
 
@@ -514,10 +791,10 @@ mod tests {
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
             println!("{:?}", verdict);
 
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
 
@@ -532,10 +809,10 @@ mod tests {
             let end_pos = PieceLoc { rank: 4, file: 5 };
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
 
@@ -552,7 +829,7 @@ mod tests {
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
 
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
         }
 
         #[test]
@@ -567,7 +844,7 @@ mod tests {
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
 
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
         }
 
         #[test]
@@ -582,7 +859,7 @@ mod tests {
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
 
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
         }
 
         #[test]
@@ -597,33 +874,68 @@ mod tests {
 
             let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
 
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(true, verdict.capturing);
+            assert!(verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
 
+        #[test]
+        fn test_invalid_move_pawn_diagonal_without_capture() {
+            let board = Board::new();
+            let piece = board.board[12].unwrap(); // E2
+
+            let verdict = super::is_valid_move(
+                &board,
+                &piece,
+                &PieceLoc::from_notation("e2").unwrap(),
+                &PieceLoc::from_notation("d3").unwrap(),
+            );
+            assert_eq!(MoveError::PawnDiagonalWithoutCapture, verdict.unwrap_err());
+
+            // An en passant target elsewhere on the board doesn't open up other squares
+            let board = super::board_from("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+            let piece = board.board[36].unwrap(); // E5
+            let verdict = super::is_valid_move(
+                &board,
+                &piece,
+                &PieceLoc::from_notation("e5").unwrap(),
+                &PieceLoc::from_notation("f6").unwrap(),
+            );
+            assert_eq!(MoveError::PawnDiagonalWithoutCapture, verdict.unwrap_err());
+        }
+
         #[test]
         fn test_valid_move_en_passant_white() {
             let mut board = setup_e4_d5();
             let white_e_pawn = board.board[28].unwrap();
             let black_f_pawn = board.board[53].unwrap();
 
-            board = board.move_piece(Move {
-                piece: white_e_pawn,
-                start_pos: PieceLoc { rank: 3, file: 4 },
-                end_pos: PieceLoc { rank: 4, file: 4 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
-            board = board.move_piece(Move {
-                piece: black_f_pawn,
-                start_pos: PieceLoc { rank: 6, file: 5 },
-                end_pos: PieceLoc { rank: 4, file: 5 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
+            board = board
+                .move_piece(Move {
+                    piece: white_e_pawn,
+                    start_pos: PieceLoc { rank: 3, file: 4 },
+                    end_pos: PieceLoc { rank: 4, file: 4 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
+            board = board
+                .move_piece(Move {
+                    piece: black_f_pawn,
+                    start_pos: PieceLoc { rank: 6, file: 5 },
+                    end_pos: PieceLoc { rank: 4, file: 5 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
 
             assert_eq!(board.move_list[0].piece.color, PieceColor::White);
             assert_eq!(board.move_list[1].piece.color, PieceColor::Black);
@@ -640,10 +952,10 @@ mod tests {
             let end_pos = PieceLoc { rank: 5, file: 5 };
 
             let verdict = super::is_valid_move(&board, &white_e_pawn, &start_pos, &end_pos);
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(true, verdict.capturing);
+            assert!(verdict.capturing);
             assert_eq!(MoveType::EnPassant, verdict.move_type);
         }
 
@@ -653,20 +965,30 @@ mod tests {
             let white_e_pawn = board.board[28].unwrap();
             let black_f_pawn = board.board[53].unwrap();
 
-            board = board.move_piece(Move {
-                piece: white_e_pawn,
-                start_pos: PieceLoc { rank: 3, file: 4 },
-                end_pos: PieceLoc { rank: 4, file: 4 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
-            board = board.move_piece(Move {
-                piece: black_f_pawn,
-                start_pos: PieceLoc { rank: 6, file: 5 },
-                end_pos: PieceLoc { rank: 4, file: 5 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
+            board = board
+                .move_piece(Move {
+                    piece: white_e_pawn,
+                    start_pos: PieceLoc { rank: 3, file: 4 },
+                    end_pos: PieceLoc { rank: 4, file: 4 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
+            board = board
+                .move_piece(Move {
+                    piece: black_f_pawn,
+                    start_pos: PieceLoc { rank: 6, file: 5 },
+                    end_pos: PieceLoc { rank: 4, file: 5 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
 
             assert_eq!(board.move_list[0].piece.color, PieceColor::White);
             assert_eq!(board.move_list[1].piece.color, PieceColor::Black);
@@ -683,10 +1005,10 @@ mod tests {
             let end_pos = PieceLoc { rank: 5, file: 5 };
 
             let verdict = super::is_valid_move(&board, &white_e_pawn, &start_pos, &end_pos);
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(true, verdict.capturing);
+            assert!(verdict.capturing);
             assert_eq!(MoveType::EnPassant, verdict.move_type);
         }
 
@@ -697,34 +1019,56 @@ mod tests {
             let black_f_pawn = board.board[53].unwrap();
             let other_white_piece = board.board[8].unwrap();
 
-            board = board.move_piece(Move {
-                piece: white_e_pawn,
-                start_pos: PieceLoc { rank: 3, file: 4 },
-                end_pos: PieceLoc { rank: 4, file: 4 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
-            board = board.move_piece(Move {
-                piece: black_f_pawn,
-                start_pos: PieceLoc { rank: 6, file: 5 },
-                end_pos: PieceLoc { rank: 5, file: 5 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
-            board = board.move_piece(Move {
-                piece: other_white_piece,
-                start_pos: PieceLoc { rank: 1, file: 0 },
-                end_pos: PieceLoc { rank: 2, file: 0 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
-            board = board.move_piece(Move {
-                piece: black_f_pawn,
-                start_pos: PieceLoc { rank: 5, file: 5 },
-                end_pos: PieceLoc { rank: 4, file: 5 },
-                move_type: MoveType::Normal,
-                capturing: false,
-            });
+            board = board
+                .move_piece(Move {
+                    piece: white_e_pawn,
+                    start_pos: PieceLoc { rank: 3, file: 4 },
+                    end_pos: PieceLoc { rank: 4, file: 4 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
+            board = board
+                .move_piece(Move {
+                    piece: black_f_pawn,
+                    start_pos: PieceLoc { rank: 6, file: 5 },
+                    end_pos: PieceLoc { rank: 5, file: 5 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
+            board = board
+                .move_piece(Move {
+                    piece: other_white_piece,
+                    start_pos: PieceLoc { rank: 1, file: 0 },
+                    end_pos: PieceLoc { rank: 2, file: 0 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
+            // The black pawn has moved once already
+            let black_f_pawn = board.board[45].unwrap();
+            board = board
+                .move_piece(Move {
+                    piece: black_f_pawn,
+                    start_pos: PieceLoc { rank: 5, file: 5 },
+                    end_pos: PieceLoc { rank: 4, file: 5 },
+                    move_type: MoveType::Normal,
+                    capturing: false,
+                    promotion: None,
+                    captured: None,
+                    captured_pos: None,
+                })
+                .unwrap();
 
             // Can only en passant pawns
             assert_eq!(white_e_pawn.piece_type, PieceType::Pawn);
@@ -734,7 +1078,91 @@ mod tests {
             let end_pos = PieceLoc { rank: 5, file: 5 };
 
             let verdict = super::is_valid_move(&board, &white_e_pawn, &start_pos, &end_pos);
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
+        }
+
+        // Plays the piece at `start` to `end`, for walking pawns into position
+        fn play(board: Board, start: PieceLoc, end: PieceLoc) -> Board {
+            let piece = board.get_piece_at_location(start).unwrap();
+            let new_move = Move::new(&board, &piece, &start, &end).unwrap();
+            board.move_piece(new_move).unwrap()
+        }
+
+        // Black's d-pawn reaches d4, then white answers a3 and a4 or e3 and e4
+        fn setup_black_d4(white_double_step: bool) -> Board {
+            let mut board = Board::new();
+            board = play(board, PieceLoc::new(1, 0), PieceLoc::new(2, 0));
+            board = play(board, PieceLoc::new(6, 3), PieceLoc::new(4, 3));
+            board = play(board, PieceLoc::new(2, 0), PieceLoc::new(3, 0));
+            board = play(board, PieceLoc::new(4, 3), PieceLoc::new(3, 3));
+            if white_double_step {
+                play(board, PieceLoc::new(1, 4), PieceLoc::new(3, 4))
+            } else {
+                board = play(board, PieceLoc::new(1, 4), PieceLoc::new(2, 4));
+                board = play(board, PieceLoc::new(7, 1), PieceLoc::new(5, 2));
+                play(board, PieceLoc::new(2, 4), PieceLoc::new(3, 4))
+            }
+        }
+
+        #[test]
+        fn test_invalid_move_pawn_double_step_over_piece() {
+            // Knight from g1 to e3, directly in front of the e-pawn
+            let board =
+                super::board_from("rnbqkbnr/pppppppp/8/8/8/4N3/PPPPPPPP/RNBQKB1R w KQkq - 0 1");
+            let piece = board.board[12].unwrap(); // E2
+
+            let start_pos = PieceLoc { rank: 1, file: 4 };
+            let end_pos = PieceLoc { rank: 3, file: 4 };
+
+            let verdict = super::is_valid_move(&board, &piece, &start_pos, &end_pos);
+            assert_eq!(
+                MoveError::PathObstructed {
+                    at: PieceLoc::new(2, 4)
+                },
+                verdict.expect_err("Knight on e3 blocks the pawn, this is invalid.")
+            );
+        }
+
+        #[test]
+        fn test_valid_move_black_en_passant_captures_white_pawn() {
+            let board = setup_black_d4(true);
+            let black_d_pawn = board.board[27].unwrap();
+
+            assert_eq!(PieceColor::Black, black_d_pawn.color);
+            assert!(board.board[28].is_some());
+
+            let start_pos = PieceLoc { rank: 3, file: 3 };
+            let end_pos = PieceLoc { rank: 2, file: 4 };
+
+            let verdict = super::is_valid_move(&board, &black_d_pawn, &start_pos, &end_pos);
+            assert!(verdict.is_ok());
+
+            let verdict = verdict.unwrap();
+            assert!(verdict.capturing);
+            assert_eq!(MoveType::EnPassant, verdict.move_type);
+
+            // The captured white pawn is taken from e4, behind the destination
+            let board = play(board, start_pos, end_pos);
+            assert!(board.board[28].is_none());
+            assert_eq!(PieceColor::Black, board.board[20].unwrap().color);
+            let capture = board.get_previous_move().unwrap();
+            assert_eq!(Some(PieceLoc::new(3, 4)), capture.captured_pos);
+            assert_eq!(PieceColor::White, capture.captured.unwrap().color);
+        }
+
+        #[test]
+        fn test_invalid_move_black_en_passant_after_1_space_move() {
+            let board = setup_black_d4(false);
+            let black_d_pawn = board.board[27].unwrap();
+
+            assert_eq!(PieceColor::Black, black_d_pawn.color);
+            assert!(board.board[28].is_some());
+
+            let start_pos = PieceLoc { rank: 3, file: 3 };
+            let end_pos = PieceLoc { rank: 2, file: 4 };
+
+            let verdict = super::is_valid_move(&board, &black_d_pawn, &start_pos, &end_pos);
+            assert!(verdict.is_err());
         }
     }
 
@@ -744,76 +1172,39 @@ mod tests {
         use crate::game::moves::move_checker::{is_knight_move, MoveType};
         use crate::game::piece::piece_info::PieceLoc;
 
+        // The b1 knight on e5, where it can take the f7 pawn
         fn setup_board_for_knight_capture() -> Board {
-            let mut board = Board::new();
-
-            board.board[36] = board.board[1];
-            board.board[1] = None;
-
-            board
+            super::board_from("rnbqkbnr/pppppppp/8/4N3/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 1")
         }
 
         #[test]
         fn test_valid_knight_move_all_directions() {
             // Up-right
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(5, 4))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(5, 4)));
             // Right-up
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 5))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 5)));
             // Right-down
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(2, 5))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(2, 5)));
             // Down-right
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(1, 4))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(1, 4)));
             // Down-left
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(1, 2))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(1, 2)));
             // Left-down
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(2, 1))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(2, 1)));
             // Left-up
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 1))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 1)));
             // Up-left
-            assert_eq!(
-                true,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(5, 2))
-            );
+            assert!(is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(5, 2)));
         }
 
         #[test]
         fn test_invalid_knight_moves() {
             // Vertical
-            assert_eq!(
-                false,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 3))
-            );
+            assert!(!is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 3)));
             // Horizontal
-            assert_eq!(
-                false,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(3, 4))
-            );
+            assert!(!is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(3, 4)));
             // Diagonal
-            assert_eq!(
-                false,
-                is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 4))
-            );
+            assert!(!is_knight_move(&PieceLoc::new(3, 3), &PieceLoc::new(4, 4)));
         }
 
         #[test]
@@ -823,14 +1214,110 @@ mod tests {
 
             let verdict =
                 super::is_valid_move(&board, &piece, &PieceLoc::new(4, 4), &PieceLoc::new(6, 5));
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
-            assert_eq!(true, verdict.capturing);
+            assert!(verdict.capturing);
             assert_eq!(MoveType::Normal, verdict.move_type);
         }
     }
 
+    #[cfg(test)]
+    mod sliding_tests {
+        use crate::game::board::Board;
+        use crate::game::moves::move_checker::{MoveError, MoveType};
+        use crate::game::piece::piece_info::PieceLoc;
+
+        #[test]
+        fn test_rook_cannot_jump_own_pawn() {
+            let board = Board::new();
+            let white_rook = board.board[0].unwrap(); // A1
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_rook,
+                &PieceLoc::new(0, 0),
+                &PieceLoc::new(4, 0),
+            );
+
+            assert_eq!(
+                MoveError::PathObstructed {
+                    at: PieceLoc::new(1, 0)
+                },
+                verdict.unwrap_err()
+            );
+        }
+
+        #[test]
+        fn test_bishop_cannot_jump_own_pawn() {
+            let board = Board::new();
+            let white_bishop = board.board[2].unwrap(); // C1
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_bishop,
+                &PieceLoc::new(0, 2),
+                &PieceLoc::new(2, 0),
+            );
+
+            assert_eq!(
+                MoveError::PathObstructed {
+                    at: PieceLoc::new(1, 1)
+                },
+                verdict.unwrap_err()
+            );
+        }
+
+        #[test]
+        fn test_queen_cannot_capture_through_piece() {
+            let mut board = Board::new();
+            // Clear the D2 pawn so the queen's only obstruction is the black D7 pawn
+            board.board[11] = None;
+            let white_queen = board.board[3].unwrap(); // D1
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_queen,
+                &PieceLoc::new(0, 3),
+                &PieceLoc::new(7, 3),
+            );
+
+            assert_eq!(
+                MoveError::PathObstructed {
+                    at: PieceLoc::new(6, 3)
+                },
+                verdict.unwrap_err()
+            );
+        }
+
+        #[test]
+        fn test_sliding_piece_with_clear_path_is_valid() {
+            let mut board = Board::new();
+            board.board[8] = None; // A2
+            board.board[11] = None; // D2
+            let white_rook = board.board[0].unwrap();
+            let white_queen = board.board[3].unwrap();
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_rook,
+                &PieceLoc::new(0, 0),
+                &PieceLoc::new(6, 0),
+            );
+            let verdict = verdict.unwrap();
+            assert!(verdict.capturing);
+            assert_eq!(MoveType::Normal, verdict.move_type);
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_queen,
+                &PieceLoc::new(0, 3),
+                &PieceLoc::new(6, 3),
+            );
+            assert!(verdict.unwrap().capturing);
+        }
+    }
+
     #[cfg(test)]
     mod king_tests {
         use crate::game::board::Board;
@@ -869,11 +1356,11 @@ mod tests {
 
             println!("{:?}", verdict);
 
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
             assert_eq!(MoveType::Castling, verdict.move_type);
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
         }
 
         #[test]
@@ -888,11 +1375,11 @@ mod tests {
                 &PieceLoc::new(0, 2),
             );
 
-            assert_eq!(true, verdict.is_ok());
+            assert!(verdict.is_ok());
 
             let verdict = verdict.unwrap();
             assert_eq!(MoveType::Castling, verdict.move_type);
-            assert_eq!(false, verdict.capturing);
+            assert!(!verdict.capturing);
         }
 
         #[test]
@@ -907,7 +1394,7 @@ mod tests {
                 &PieceLoc::new(0, 2),
             );
 
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
             assert_eq!(
                 MoveError::CannotCastleThroughPiece,
                 verdict.expect_err("Piece in-between king and rook, this is invalid.")
@@ -932,7 +1419,7 @@ mod tests {
                 &PieceLoc::new(0, 2),
             );
 
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
             assert_eq!(
                 MoveError::CannotCastleWithMovedRook,
                 verdict.expect_err("Queenside rook moved, this is invalid.")
@@ -958,11 +1445,61 @@ mod tests {
                 &PieceLoc::new(0, 2),
             );
 
-            assert_eq!(false, verdict.is_ok());
+            assert!(verdict.is_err());
             assert_eq!(
                 MoveError::CannotCastleWithMovedKing,
                 verdict.expect_err("King has moved, this is invalid.")
             );
         }
+
+        #[test]
+        fn test_castle_out_of_check_is_invalid() {
+            let mut board = setup_back_rank();
+            // Open the e-file and put a black rook on it
+            board.board[12] = None;
+            board.board[36] = board.board[63];
+            let white_king = board.board[4].unwrap();
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_king,
+                &PieceLoc::new(0, 4),
+                &PieceLoc::new(0, 6),
+            );
+
+            assert_eq!(
+                MoveError::CannotCastleOutOfCheck,
+                verdict.expect_err("King is in check, this is invalid.")
+            );
+        }
+
+        #[test]
+        fn test_castle_through_check_is_invalid() {
+            let mut board = setup_back_rank();
+            // A black rook on the open f-file attacks f1, which the king passes over
+            board.board[13] = None;
+            board.board[37] = board.board[63];
+            let white_king = board.board[4].unwrap();
+
+            let verdict = super::is_valid_move(
+                &board,
+                &white_king,
+                &PieceLoc::new(0, 4),
+                &PieceLoc::new(0, 6),
+            );
+            assert_eq!(
+                MoveError::CannotCastleThroughCheck,
+                verdict.expect_err("King passes through check, this is invalid.")
+            );
+
+            // The queenside path is unaffected
+            let verdict = super::is_valid_move(
+                &board,
+                &white_king,
+                &PieceLoc::new(0, 4),
+                &PieceLoc::new(0, 2),
+            );
+            assert!(verdict.is_ok());
+        }
     }
 }
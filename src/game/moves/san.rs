@@ -0,0 +1,443 @@
+use core::fmt;
+
+use crate::game::board::{board_display, Board, CheckKind};
+use crate::game::piece::piece_info::{PieceLoc, PieceType};
+
+use super::{move_checker::MoveType, Move};
+
+#[derive(Debug, PartialEq)]
+pub enum SanError {
+    Empty,
+    InvalidFormat,
+    NoMatchingMove,
+    AmbiguousMove,
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match *self {
+            SanError::Empty => "No move was given.",
+            SanError::InvalidFormat => "The move could not be read as algebraic notation.",
+            SanError::NoMatchingMove => "No legal move matches that notation.",
+            SanError::AmbiguousMove => {
+                "More than one legal move matches that notation, include the origin square."
+            }
+        };
+        write!(f, "Invalid SAN: {}", output)
+    }
+}
+
+// The pieces of a non-castling SAN string, i.e. "Nbxd2", "Qe2xe5" or "exf8=Q"
+struct SanPattern {
+    piece_type: PieceType,
+    from_file: Option<u8>,
+    from_rank: Option<u8>,
+    capturing: bool,
+    dest: PieceLoc,
+    promotion: Option<PieceType>,
+}
+
+impl SanPattern {
+    fn parse(san: &str, board: &Board) -> Result<SanPattern, SanError> {
+        let mut chars: Vec<char> = san.chars().collect();
+
+        let piece_type = chars
+            .first()
+            .and_then(|&c| parse_piece_letter(c))
+            .unwrap_or(PieceType::Pawn);
+        if piece_type != PieceType::Pawn {
+            chars.remove(0);
+        }
+
+        // Promotions are written "e8=Q", though the "=" is often left out
+        let promotion = match chars.last().and_then(|&c| parse_piece_letter(c)) {
+            Some(promotion) if piece_type == PieceType::Pawn => {
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(promotion)
+            }
+            _ => None,
+        };
+
+        // The destination square is always last, its file letters followed by rank digits
+        let rest: String = chars.into_iter().collect();
+        let (rest, dest_rank) = split_rank(&rest);
+        let dest_rank = parse_rank(dest_rank).ok_or(SanError::InvalidFormat)?;
+        let (rest, dest_file) =
+            split_dest_file(rest, board.files).ok_or(SanError::InvalidFormat)?;
+        let dest = PieceLoc::new(dest_rank, dest_file);
+        if !dest.is_within(board.ranks, board.files) {
+            return Err(SanError::InvalidFormat);
+        }
+
+        let capturing = rest.ends_with('x');
+        let rest = rest.strip_suffix('x').unwrap_or(rest);
+
+        // Whatever remains is a hint at the origin square, which may be over-qualified
+        let (rest, from_rank) = split_rank(rest);
+        let (rest, from_file) = split_file(rest);
+        if !rest.is_empty() {
+            return Err(SanError::InvalidFormat);
+        }
+        let from_rank = match from_rank {
+            "" => None,
+            rank => Some(parse_rank(rank).ok_or(SanError::InvalidFormat)?),
+        };
+        let from_file = match from_file {
+            "" => None,
+            file => Some(board_display::file_from_name(file).ok_or(SanError::InvalidFormat)?),
+        };
+
+        Ok(SanPattern {
+            piece_type,
+            from_file,
+            from_rank,
+            capturing,
+            dest,
+            promotion,
+        })
+    }
+
+    fn matches(&self, m: &Move) -> bool {
+        m.piece.piece_type == self.piece_type
+            && m.move_type != MoveType::Castling
+            && m.end_pos == self.dest
+            && self.from_file.is_none_or(|file| file == m.start_pos.file)
+            && self.from_rank.is_none_or(|rank| rank == m.start_pos.rank)
+            && (m.capturing || !self.capturing)
+            && m.promotion == self.promotion
+    }
+}
+
+// Pawns go unlettered in SAN, so only the other pieces' letters name a piece
+fn parse_piece_letter(c: char) -> Option<PieceType> {
+    PieceType::from_char(c).filter(|&piece_type| piece_type != PieceType::Pawn)
+}
+
+// Splits the trailing file letters off `san`. Files are always lowercase in SAN, since an
+// uppercase B names a bishop, and an 'x' is always read as a capture.
+fn split_file(san: &str) -> (&str, &str) {
+    let rest = san.trim_end_matches(|c: char| c.is_ascii_lowercase() && c != 'x');
+    san.split_at(rest.len())
+}
+
+// The destination's letters may run straight on from a file hint, as in "Nbd2", so its file
+// is the longest run of trailing letters that still names a file on the board
+fn split_dest_file(san: &str, files: u8) -> Option<(&str, u8)> {
+    let (rest, letters) = split_file(san);
+    (0..letters.len()).find_map(|start| {
+        board_display::file_from_name(&letters[start..])
+            .filter(|&file| file < files)
+            .map(|file| (&san[..rest.len() + start], file))
+    })
+}
+
+fn split_rank(san: &str) -> (&str, &str) {
+    let rest = san.trim_end_matches(|c: char| c.is_ascii_digit());
+    san.split_at(rest.len())
+}
+
+fn parse_rank(rank: &str) -> Option<u8> {
+    match rank.parse::<u8>() {
+        Ok(rank @ 1..) => Some(rank - 1),
+        _ => None,
+    }
+}
+
+// Smooths over the common variations in hand-written SAN: zeroes for castling, trailing
+// check and annotation marks, and an "e.p." suffix on en passant captures.
+fn normalize_san(san: &str) -> String {
+    let annotations: &[char] = &['+', '#', '!', '?'];
+
+    // Only castling is written with zeroes, rank 10 and up have them too
+    let normalized = match san.trim() {
+        castling if castling.starts_with('0') => castling.replace('0', "O"),
+        san => san.to_string(),
+    };
+    let normalized = normalized.trim_end_matches(annotations);
+    let normalized = normalized.strip_suffix("e.p.").unwrap_or(normalized);
+    normalized
+        .trim_end()
+        .trim_end_matches(annotations)
+        .to_string()
+}
+
+impl Move {
+    /// Finds the legal move on `board` described by a SAN string such as "Nf3" or "exd5".
+    ///
+    /// Common variants are accepted too: "0-0" for castling, missing or extra check marks,
+    /// "e.p." suffixes, promotions without the "=", and over-qualified origins like "Qe2xe5".
+    pub fn from_san(board: &Board, san: &str) -> Result<Move, SanError> {
+        let san = normalize_san(san);
+        if san.is_empty() {
+            return Err(SanError::Empty);
+        }
+
+        let mut candidates: Vec<Move> = if san == "O-O" || san == "O-O-O" {
+            let kingside = san == "O-O";
+            board
+                .legal_moves()
+                .into_iter()
+                .filter(|m| {
+                    m.move_type == MoveType::Castling
+                        && (m.end_pos.file > m.start_pos.file) == kingside
+                })
+                .collect()
+        } else {
+            let pattern = SanPattern::parse(&san, board)?;
+            board
+                .legal_moves()
+                .into_iter()
+                .filter(|m| pattern.matches(m))
+                .collect()
+        };
+
+        match candidates.len() {
+            0 => Err(SanError::NoMatchingMove),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(SanError::AmbiguousMove),
+        }
+    }
+
+    /// Renders the move in Standard Algebraic Notation, i.e. "Nf3", "exd5", "O-O" or "Qh5+".
+    ///
+    /// SAN depends on the position the move is played from, so `board` must be the board
+    /// *before* this move has been applied.
+    pub fn to_san(&self, board: &Board) -> String {
+        let mut san = String::new();
+
+        if self.move_type == MoveType::Castling {
+            if self.end_pos.file > self.start_pos.file {
+                san.push_str("O-O");
+            } else {
+                san.push_str("O-O-O");
+            }
+        } else {
+            if self.piece.piece_type == PieceType::Pawn {
+                if self.capturing {
+                    san.push_str(&board_display::file_name(self.start_pos.file));
+                }
+            } else {
+                san.push(board_display::get_piece_display(&self.piece, true));
+                san.push_str(&self.get_disambiguation(board));
+            }
+
+            if self.capturing {
+                san.push('x');
+            }
+            san.push_str(&self.end_pos.to_string());
+
+            if let Some(promotion) = self.promotion {
+                san.push('=');
+                san.push(promotion.to_char());
+            }
+        }
+
+        match board.move_check_kind(self) {
+            Some(CheckKind::Checkmate) => san.push('#'),
+            Some(CheckKind::Check) => san.push('+'),
+            None => (),
+        }
+        san
+    }
+
+    // When another identical piece could also reach the destination, the origin file is
+    // preferred to tell them apart, then the origin rank, then the full origin square.
+    fn get_disambiguation(&self, board: &Board) -> String {
+        let rivals: Vec<PieceLoc> = board
+            .legal_moves()
+            .into_iter()
+            .filter(|m| {
+                m.piece.piece_type == self.piece.piece_type
+                    && m.end_pos == self.end_pos
+                    && m.start_pos != self.start_pos
+            })
+            .map(|m| m.start_pos)
+            .collect();
+
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals.iter().all(|loc| loc.file != self.start_pos.file) {
+            board_display::file_name(self.start_pos.file)
+        } else if rivals.iter().all(|loc| loc.rank != self.start_pos.rank) {
+            (self.start_pos.rank + 1).to_string()
+        } else {
+            self.start_pos.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::{piece_info::PieceColor, Piece};
+
+    fn play(sans: &[&str]) -> Board {
+        let mut board = Board::new();
+        for san in sans {
+            let m = Move::from_san(&board, san).expect("Setup move should be legal");
+            board = board.move_piece(m).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn test_from_san_pawn_and_piece_moves() {
+        let board = Board::new();
+
+        let m = Move::from_san(&board, "e4").unwrap();
+        assert_eq!(PieceType::Pawn, m.piece.piece_type);
+        assert_eq!(PieceLoc::new(1, 4), m.start_pos);
+        assert_eq!(PieceLoc::new(3, 4), m.end_pos);
+
+        let m = Move::from_san(&board, "Nf3").unwrap();
+        assert_eq!(PieceType::Knight, m.piece.piece_type);
+        assert_eq!(PieceLoc::new(0, 6), m.start_pos);
+        assert_eq!(PieceLoc::new(2, 5), m.end_pos);
+    }
+
+    #[test]
+    fn test_from_san_disambiguates_by_file() {
+        let board = play(&["d4", "d5", "Nf3", "Nf6", "e3", "e6"]);
+
+        let m = Move::from_san(&board, "Nbd2").unwrap();
+        assert_eq!(PieceLoc::new(0, 1), m.start_pos);
+        assert_eq!(PieceLoc::new(1, 3), m.end_pos);
+        assert_eq!(
+            SanError::AmbiguousMove,
+            Move::from_san(&board, "Nd2").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_from_san_pawn_capture() {
+        let board = play(&["e4", "d5"]);
+
+        let m = Move::from_san(&board, "exd5").unwrap();
+        assert!(m.capturing);
+        assert_eq!(PieceLoc::new(3, 4), m.start_pos);
+        assert_eq!(PieceLoc::new(4, 3), m.end_pos);
+    }
+
+    #[test]
+    fn test_from_san_castling() {
+        let board = play(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"]);
+
+        let m = Move::from_san(&board, "O-O").unwrap();
+        assert_eq!(MoveType::Castling, m.move_type);
+        assert_eq!(PieceLoc::new(0, 6), m.end_pos);
+    }
+
+    #[test]
+    fn test_from_san_promotion() {
+        let mut pawn = Piece::new(PieceType::Pawn, PieceColor::White);
+        pawn.has_moved = true;
+        let board = Board::empty(8, 8)
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::King, PieceColor::White),
+            )
+            .with_piece(
+                PieceLoc::new(7, 7),
+                Piece::new(PieceType::King, PieceColor::Black),
+            )
+            .with_piece(PieceLoc::new(6, 4), pawn);
+
+        let m = Move::from_san(&board, "e8=Q").unwrap();
+        assert_eq!(PieceLoc::new(7, 4), m.end_pos);
+        assert_eq!(Some(PieceType::Queen), m.promotion);
+
+        let m = Move::from_san(&board, "e8N").unwrap();
+        assert_eq!(Some(PieceType::Knight), m.promotion);
+
+        assert_eq!(
+            SanError::NoMatchingMove,
+            Move::from_san(&board, "e8").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_from_san_castling_with_zeroes() {
+        let board = play(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"]);
+
+        let m = Move::from_san(&board, "0-0").unwrap();
+        assert_eq!(MoveType::Castling, m.move_type);
+        assert_eq!(PieceLoc::new(0, 6), m.end_pos);
+    }
+
+    #[test]
+    fn test_from_san_en_passant_suffix() {
+        let board = play(&["e4", "a6", "e5", "d5"]);
+
+        let m = Move::from_san(&board, "exd6e.p.").unwrap();
+        assert_eq!(MoveType::EnPassant, m.move_type);
+        assert_eq!(PieceLoc::new(4, 4), m.start_pos);
+        assert_eq!(PieceLoc::new(5, 3), m.end_pos);
+    }
+
+    #[test]
+    fn test_from_san_over_qualified_origin() {
+        let board = play(&["e4", "d5", "exd5", "e5", "Qe2", "Nc6"]);
+
+        let m = Move::from_san(&board, "Qe2xe5").unwrap();
+        assert_eq!(PieceType::Queen, m.piece.piece_type);
+        assert_eq!(PieceLoc::new(1, 4), m.start_pos);
+        assert_eq!(PieceLoc::new(4, 4), m.end_pos);
+        assert!(m.capturing);
+    }
+
+    #[test]
+    fn test_from_san_missing_check_marker() {
+        let board = play(&["e4", "d6"]);
+
+        let m = Move::from_san(&board, "Bb5").unwrap();
+        assert_eq!(PieceLoc::new(4, 1), m.end_pos);
+        assert_eq!("Bb5+", m.to_san(&board));
+    }
+
+    #[test]
+    fn test_from_san_rejects_ambiguous_and_illegal_moves() {
+        let board = play(&["e4", "e5", "Nc3", "Nc6"]);
+
+        assert_eq!(
+            SanError::AmbiguousMove,
+            Move::from_san(&board, "Ne2").unwrap_err()
+        );
+        assert!(Move::from_san(&board, "Nge2").is_ok());
+        assert_eq!(
+            SanError::NoMatchingMove,
+            Move::from_san(&board, "Ke3").unwrap_err()
+        );
+        assert_eq!(
+            SanError::InvalidFormat,
+            Move::from_san(&board, "Nz9").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_from_san_on_a_ten_rank_board() {
+        let board = Board::empty(10, 8)
+            .with_piece(
+                PieceLoc::new(0, 4),
+                Piece::new(PieceType::King, PieceColor::White),
+            ) // E1
+            .with_piece(
+                PieceLoc::new(0, 0),
+                Piece::new(PieceType::Rook, PieceColor::White),
+            ) // A1
+            .with_piece(
+                PieceLoc::new(9, 7),
+                Piece::new(PieceType::King, PieceColor::Black),
+            ); // H10
+
+        let m = Move::from_san(&board, "Ra10").unwrap();
+        assert_eq!(PieceLoc::new(9, 0), m.end_pos);
+        assert_eq!("Ra10+", m.to_san(&board));
+        assert_eq!(
+            SanError::InvalidFormat,
+            Move::from_san(&board, "Ra11").unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use core::fmt;
+
+use crate::game::board::Board;
+use crate::game::piece::piece_info::PieceType;
+
+use super::{Move, PROMOTION_PIECE_TYPES};
+
+#[derive(Debug, PartialEq)]
+pub enum UciMoveError {
+    WrongLength,
+    BadSquare,
+    BadPromotion,
+    NoMatchingMove,
+}
+
+impl fmt::Display for UciMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match *self {
+            UciMoveError::WrongLength => {
+                "A move must be two squares and an optional promotion, i.e. e2e4 or e7e8q."
+            }
+            UciMoveError::BadSquare => "The move's squares must each be a file and rank, i.e. e2.",
+            UciMoveError::BadPromotion => "A promotion must be one of q, r, b, or n.",
+            UciMoveError::NoMatchingMove => "No legal move matches that notation.",
+        };
+        write!(f, "Invalid UCI Move: {}", output)
+    }
+}
+
+// Splits the square at the front of a move, its file letters and then its rank digits,
+// from the rest of the move
+fn split_square(notation: &str) -> Option<(&str, &str)> {
+    let letters = notation.find(|c: char| !c.is_ascii_alphabetic())?;
+    let digits = notation[letters..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(notation.len(), |end| letters + end);
+    (letters > 0 && digits > letters).then(|| notation.split_at(digits))
+}
+
+// Promotions are written with the piece's letter in lowercase
+fn promotion_letter(piece_type: PieceType) -> Option<char> {
+    PROMOTION_PIECE_TYPES
+        .contains(&piece_type)
+        .then(|| piece_type.to_char().to_ascii_lowercase())
+}
+
+impl Move {
+    /// Finds the legal move on `board` given in UCI's long algebraic notation: the start
+    /// and end squares, plus a lowercase piece letter for promotions, i.e. "e2e4" or "e7e8q".
+    /// Castling is written as the king's move, "e1g1". Squares past the eighth rank or
+    /// the h-file are read too, for larger boards.
+    pub fn from_uci(board: &Board, notation: &str) -> Result<Move, UciMoveError> {
+        if !notation.is_ascii() {
+            return Err(UciMoveError::WrongLength);
+        }
+        let (start, rest) = split_square(notation).ok_or(UciMoveError::WrongLength)?;
+        let (end, rest) = split_square(rest).ok_or(UciMoveError::WrongLength)?;
+        if rest.len() > 1 {
+            return Err(UciMoveError::WrongLength);
+        }
+        let start = board.parse_square(start).or(Err(UciMoveError::BadSquare))?;
+        let end = board.parse_square(end).or(Err(UciMoveError::BadSquare))?;
+        let promotion = match rest.chars().next() {
+            Some(c) => Some(
+                PROMOTION_PIECE_TYPES
+                    .into_iter()
+                    .find(|&piece_type| promotion_letter(piece_type) == Some(c))
+                    .ok_or(UciMoveError::BadPromotion)?,
+            ),
+            None => None,
+        };
+
+        board
+            .legal_moves()
+            .into_iter()
+            .find(|m| m.start_pos == start && m.end_pos == end && m.promotion == promotion)
+            .ok_or(UciMoveError::NoMatchingMove)
+    }
+
+    /// Renders the move in UCI's long algebraic notation, the inverse of `from_uci`.
+    pub fn to_uci(&self) -> String {
+        let mut notation = format!("{}{}", self.start_pos, self.end_pos);
+        if let Some(letter) = self.promotion.and_then(promotion_letter) {
+            notation.push(letter);
+        }
+        notation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::piece::piece_info::PieceLoc;
+
+    #[test]
+    fn test_normal_move_round_trip() {
+        let board = Board::new();
+        let m = Move::from_uci(&board, "g1f3").unwrap();
+
+        assert_eq!(Move::from_san(&board, "Nf3").unwrap(), m);
+        assert_eq!("g1f3", m.to_uci());
+    }
+
+    #[test]
+    fn test_promotion_round_trip() {
+        let board = Board::from_fen("8/P6k/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let m = Move::from_uci(&board, "a7a8n").unwrap();
+
+        assert_eq!(Some(PieceType::Knight), m.promotion);
+        assert_eq!("a7a8n", m.to_uci());
+        assert_eq!(
+            UciMoveError::NoMatchingMove,
+            Move::from_uci(&board, "a7a8").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_castling_is_king_to_square() {
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        let m = Move::from_san(&board, "O-O").unwrap();
+        assert_eq!("e1g1", m.to_uci());
+        assert_eq!(m, Move::from_uci(&board, "e1g1").unwrap());
+        assert_eq!("e1c1", Move::from_san(&board, "O-O-O").unwrap().to_uci());
+    }
+
+    #[test]
+    fn test_from_uci_rejects_malformed_input() {
+        let board = Board::new();
+
+        assert_eq!(
+            UciMoveError::WrongLength,
+            Move::from_uci(&board, "e2").unwrap_err()
+        );
+        assert_eq!(
+            UciMoveError::BadSquare,
+            Move::from_uci(&board, "e9e4").unwrap_err()
+        );
+        assert_eq!(
+            UciMoveError::BadPromotion,
+            Move::from_uci(&board, "e2e4k").unwrap_err()
+        );
+        assert_eq!(
+            UciMoveError::NoMatchingMove,
+            Move::from_uci(&board, "e2e5").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_two_digit_ranks_on_a_ten_rank_board() {
+        let board = Board::with_size(10, 8);
+        let m = Move::from_uci(&board, "b1c3").unwrap();
+        let board = board.move_piece(m).unwrap();
+
+        let m = Move::from_uci(&board, "b10c8").unwrap();
+        assert_eq!(PieceLoc::new(9, 1), m.start_pos);
+        assert_eq!("b10c8", m.to_uci());
+        assert_eq!(
+            UciMoveError::BadSquare,
+            Move::from_uci(&board, "b11c8").unwrap_err()
+        );
+    }
+}
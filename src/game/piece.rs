@@ -6,6 +6,7 @@ pub mod piece_info {
     use crate::game::board::board_display;
 
     #[derive(Copy, Debug, Clone, Hash, Eq, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum PieceType {
         Pawn,
         Knight,
@@ -15,15 +16,59 @@ pub mod piece_info {
         King,
     }
 
+    impl PieceType {
+        /// The uppercase letter that stands for this piece in FEN and SAN, i.e. 'N' for a
+        /// knight. Pawns are 'P', even though SAN leaves them unlettered.
+        pub fn to_char(&self) -> char {
+            match self {
+                PieceType::Pawn => 'P',
+                PieceType::Knight => 'N',
+                PieceType::Bishop => 'B',
+                PieceType::Rook => 'R',
+                PieceType::Queen => 'Q',
+                PieceType::King => 'K',
+            }
+        }
+
+        /// The piece type an uppercase letter stands for, the inverse of `to_char`.
+        pub fn from_char(c: char) -> Option<PieceType> {
+            match c {
+                'P' => Some(PieceType::Pawn),
+                'N' => Some(PieceType::Knight),
+                'B' => Some(PieceType::Bishop),
+                'R' => Some(PieceType::Rook),
+                'Q' => Some(PieceType::Queen),
+                'K' => Some(PieceType::King),
+                _ => None,
+            }
+        }
+
+        /// The standard worth of the piece in centipawns. Kings can never be traded, so
+        /// they count for nothing.
+        pub fn value(&self) -> u32 {
+            match self {
+                PieceType::Pawn => 100,
+                PieceType::Knight => 320,
+                PieceType::Bishop => 330,
+                PieceType::Rook => 500,
+                PieceType::Queen => 900,
+                PieceType::King => 0,
+            }
+        }
+    }
+
     impl fmt::Display for PieceType {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             write!(f, "{:?}", self)
         }
     }
 
-    #[derive(Copy, Debug, Clone, Eq, Hash, PartialEq)]
+    #[derive(Copy, Debug, Default, Clone, Eq, Hash, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum PieceColor {
         Black,
+        // White always moves first
+        #[default]
         White,
     }
 
@@ -42,7 +87,30 @@ pub mod piece_info {
         }
     }
 
-    #[derive(Copy, Clone, PartialEq)]
+    #[derive(Debug, PartialEq)]
+    pub enum NotationError {
+        WrongLength,
+        BadFile,
+        BadRank,
+        OutOfBounds,
+    }
+
+    impl fmt::Display for NotationError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let output = match *self {
+                NotationError::WrongLength => {
+                    "A square must be given as a file and a rank, i.e. E4."
+                }
+                NotationError::BadFile => "The file must be a letter naming a file on the board.",
+                NotationError::BadRank => "The rank must be a number.",
+                NotationError::OutOfBounds => "That square is not on the board.",
+            };
+            write!(f, "Invalid Notation: {}", output)
+        }
+    }
+
+    #[derive(Copy, Clone, Eq, Hash, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PieceLoc {
         pub rank: u8,
         pub file: u8,
@@ -53,34 +121,70 @@ pub mod piece_info {
             PieceLoc { rank, file }
         }
 
-        pub fn from_notation(notation: &str) -> Option<PieceLoc> {
-            if notation.len() != 2 {
-                println!("Wrong number of chars");
-                return None;
+        // Reads a square of a standard board written as a file letter and rank number, i.e.
+        // "E4" or "e4"
+        pub fn from_notation(notation: &str) -> Result<PieceLoc, NotationError> {
+            PieceLoc::from_notation_on(notation, 8, 8)
+        }
+
+        /// Reads a square of a board with the given number of ranks and files, written as
+        /// its file letters and rank number, i.e. "e4", or "j10" on a larger board. Files
+        /// past 'z' are named "aa", "ab" and so on.
+        pub fn from_notation_on(
+            notation: &str,
+            ranks: u8,
+            files: u8,
+        ) -> Result<PieceLoc, NotationError> {
+            // A rank never takes more digits than the board's last rank does
+            let max_digits = ranks.to_string().len();
+            let letters = notation
+                .chars()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .count();
+            let (file_name, rank_number) = notation.split_at(letters);
+            if notation.chars().count() < 2 {
+                return Err(NotationError::WrongLength);
+            }
+            if file_name.is_empty() {
+                return Err(NotationError::BadFile);
+            }
+            if rank_number.len() > max_digits {
+                return Err(NotationError::WrongLength);
             }
 
-            let mut chars = notation.chars();
-            if let (Some(file), Some(rank)) = (
-                board_display::convert_rank_alpha_to_numeric(chars.next()?),
-                chars.next()?.to_digit(10),
-            ) {
-                let rank = (rank as u8) - 1;
-                if PieceLoc::is_valid(rank, file) {
-                    return Some(PieceLoc::new(rank, file));
-                }
-                println!("Got invalid location");
+            let rank: u8 = rank_number.parse().map_err(|_| NotationError::BadRank)?;
+            let file = board_display::file_from_name(file_name)
+                .filter(|&file| file < files)
+                .ok_or(NotationError::BadFile)?;
+            let loc = PieceLoc::new(rank.wrapping_sub(1), file);
+            if rank == 0 || !loc.is_within(ranks, files) {
+                return Err(NotationError::OutOfBounds);
             }
-            println!("Couldn't convert input to PieceLoc");
-            None
+            Ok(loc)
         }
 
-        pub fn is_valid(rank: u8, file: u8) -> bool {
-            // If both values are valid u8's and within the board's size, return a valid location
-            if rank <= 7 && file <= 7 {
-                true
-            } else {
-                false
-            }
+        pub fn from_notation_opt(notation: &str) -> Option<PieceLoc> {
+            PieceLoc::from_notation(notation).ok()
+        }
+
+        /// Whether this is a light square. The corner square a1 is dark, and the colors
+        /// alternate from there, so h1 and a8 are light.
+        pub fn is_light(&self) -> bool {
+            // The low bits alone decide it, and unlike a sum they can't overflow
+            (self.rank ^ self.file) & 1 == 1
+        }
+
+        /// Whether the square lies on a board with the given number of ranks and files.
+        pub fn is_within(&self, ranks: u8, files: u8) -> bool {
+            self.rank < ranks && self.file < files
+        }
+    }
+
+    // Renders the square in algebraic form, i.e. "e4"
+    impl fmt::Display for PieceLoc {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let rank = u16::from(self.rank) + 1;
+            write!(f, "{}{}", board_display::file_name(self.file), rank)
         }
     }
 
@@ -95,6 +199,7 @@ pub mod piece_info {
 }
 
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub piece_type: piece_info::PieceType,
     pub color: piece_info::PieceColor,
@@ -130,3 +235,130 @@ impl fmt::Debug for Piece {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::piece_info::{NotationError, PieceColor, PieceLoc, PieceType};
+    use super::*;
+
+    #[test]
+    fn test_square_colors() {
+        for (notation, light) in [
+            ("a1", false),
+            ("h1", true),
+            ("a8", true),
+            ("h8", false),
+            ("d1", true),
+            ("e1", false),
+            ("e4", true),
+            ("d4", false),
+        ] {
+            let loc = PieceLoc::from_notation(notation).unwrap();
+            assert_eq!(light, loc.is_light(), "{}", notation);
+        }
+        // Squares of the largest boards
+        assert!(!PieceLoc::new(200, 100).is_light());
+        assert!(PieceLoc::new(255, 254).is_light());
+    }
+
+    #[test]
+    fn test_piece_type_chars_round_trip() {
+        for piece_type in [
+            PieceType::Pawn,
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+            PieceType::King,
+        ] {
+            let c = piece_type.to_char();
+            assert!(c.is_ascii_uppercase());
+            assert_eq!(Some(piece_type), PieceType::from_char(c));
+        }
+        assert_eq!(None, PieceType::from_char('n'));
+        assert_eq!(None, PieceType::from_char('X'));
+    }
+
+    #[test]
+    fn test_piece_type_values() {
+        assert_eq!(100, PieceType::Pawn.value());
+        assert_eq!(900, PieceType::Queen.value());
+        assert_eq!(0, PieceType::King.value());
+        assert!(PieceType::Bishop.value() > PieceType::Knight.value());
+    }
+
+    #[test]
+    fn test_new_piece_has_not_moved() {
+        let piece = Piece::new(PieceType::Rook, PieceColor::Black);
+
+        assert_eq!(PieceType::Rook, piece.piece_type);
+        assert_eq!(PieceColor::Black, piece.color);
+        assert!(!piece.has_moved);
+    }
+
+    #[test]
+    fn test_from_notation() {
+        assert_eq!(Ok(PieceLoc::new(3, 4)), PieceLoc::from_notation("E4"));
+        assert_eq!(Ok(PieceLoc::new(7, 0)), PieceLoc::from_notation("a8"));
+        assert_eq!(Some(PieceLoc::new(0, 7)), PieceLoc::from_notation_opt("H1"));
+    }
+
+    #[test]
+    fn test_from_notation_errors() {
+        assert_eq!(
+            Err(NotationError::WrongLength),
+            PieceLoc::from_notation("E44")
+        );
+        assert_eq!(Err(NotationError::WrongLength), PieceLoc::from_notation(""));
+        assert_eq!(Err(NotationError::BadFile), PieceLoc::from_notation("+4"));
+        assert_eq!(Err(NotationError::BadFile), PieceLoc::from_notation("Z4"));
+        assert_eq!(Err(NotationError::BadRank), PieceLoc::from_notation("EE"));
+        assert_eq!(
+            Err(NotationError::OutOfBounds),
+            PieceLoc::from_notation("E9")
+        );
+        assert_eq!(
+            Err(NotationError::OutOfBounds),
+            PieceLoc::from_notation("E0")
+        );
+        assert_eq!(None, PieceLoc::from_notation_opt("E0"));
+    }
+
+    #[test]
+    fn test_piece_loc_display_is_algebraic() {
+        assert_eq!("e4", PieceLoc::new(3, 4).to_string());
+        assert_eq!("a1", PieceLoc::new(0, 0).to_string());
+        assert_eq!("h8", PieceLoc::new(7, 7).to_string());
+        // Squares beyond a standard board, for larger variants
+        assert_eq!("j10", PieceLoc::new(9, 9).to_string());
+        assert_eq!("aa1", PieceLoc::new(0, 26).to_string());
+        assert_eq!("iv256", PieceLoc::new(255, 255).to_string());
+    }
+
+    #[test]
+    fn test_from_notation_on_larger_boards() {
+        assert_eq!(
+            Ok(PieceLoc::new(9, 9)),
+            PieceLoc::from_notation_on("J10", 10, 10)
+        );
+        assert_eq!(
+            Ok(PieceLoc::new(0, 26)),
+            PieceLoc::from_notation_on("aa1", 8, 30)
+        );
+        assert_eq!(
+            Err(NotationError::OutOfBounds),
+            PieceLoc::from_notation_on("a11", 10, 10)
+        );
+        assert_eq!(
+            Err(NotationError::BadFile),
+            PieceLoc::from_notation_on("k1", 10, 10)
+        );
+        // Every square names itself, even on the largest board
+        for loc in [PieceLoc::new(9, 9), PieceLoc::new(254, 254)] {
+            assert_eq!(
+                Ok(loc),
+                PieceLoc::from_notation_on(&loc.to_string(), 255, 255)
+            );
+        }
+    }
+}
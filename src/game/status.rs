@@ -0,0 +1,187 @@
+use core::fmt;
+
+use crate::game::piece::piece_info::PieceColor;
+
+/// Why a game was drawn by the rules, as opposed to by the players agreeing to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrawReason {
+    Stalemate,
+    // Neither side has the material left to ever checkmate
+    InsufficientMaterial,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+}
+
+impl DrawReason {
+    /// Whether the draw only happens if a player claims it. The fifty-move rule and
+    /// threefold repetition must be claimed, while every other draw ends the game at once.
+    pub fn is_claimable(self) -> bool {
+        matches!(
+            self,
+            DrawReason::FiftyMoveRule | DrawReason::ThreefoldRepetition
+        )
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let output = match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::FiftyMoveRule => "the fifty-move rule",
+            DrawReason::SeventyFiveMoveRule => "the seventy-five-move rule",
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+            DrawReason::FivefoldRepetition => "fivefold repetition",
+        };
+        f.write_str(output)
+    }
+}
+
+/// Where a game stands. A claimable `Draw` is reported as soon as a player could claim
+/// it, but the game goes on until someone does, when it becomes `DrawClaimed`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameStatus {
+    #[default]
+    Ongoing,
+    Checkmate {
+        winner: PieceColor,
+    },
+    Draw {
+        reason: DrawReason,
+        claimable: bool,
+    },
+    Resignation {
+        winner: PieceColor,
+    },
+    DrawAgreed,
+    DrawClaimed {
+        reason: DrawReason,
+    },
+    Timeout {
+        winner: PieceColor,
+    },
+}
+
+impl GameStatus {
+    /// A draw for the given reason, claimable or not as the rules have it.
+    pub fn draw(reason: DrawReason) -> GameStatus {
+        GameStatus::Draw {
+            reason,
+            claimable: reason.is_claimable(),
+        }
+    }
+
+    /// Whether the game has ended. A draw that must be claimed doesn't end it on its own.
+    pub fn is_over(&self) -> bool {
+        !matches!(
+            self,
+            GameStatus::Ongoing
+                | GameStatus::Draw {
+                    claimable: true,
+                    ..
+                }
+        )
+    }
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameStatus::Ongoing => write!(f, "The game is still in progress."),
+            GameStatus::Checkmate { winner } => write!(f, "Checkmate! {} wins.", winner),
+            GameStatus::Draw {
+                reason: DrawReason::Stalemate,
+                ..
+            } => write!(f, "Stalemate! The game is a draw."),
+            GameStatus::Draw {
+                reason,
+                claimable: true,
+            } => write!(f, "A draw can be claimed by {}.", reason),
+            GameStatus::Draw { reason, .. } => write!(f, "The game is a draw by {}.", reason),
+            GameStatus::Resignation { winner } => {
+                write!(f, "{} resigns. {} wins.", winner.flip(), winner)
+            }
+            GameStatus::DrawAgreed => write!(f, "Draw agreed. The game is a draw."),
+            GameStatus::DrawClaimed { reason } => {
+                write!(f, "Draw claimed by {}. The game is a draw.", reason)
+            }
+            GameStatus::Timeout { winner } => {
+                write!(f, "{} ran out of time. {} wins.", winner.flip(), winner)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Board;
+    use crate::game::moves::Move;
+
+    // Shuffles both knights out and back `times` times from the starting position
+    fn shuffle_knights(times: usize) -> Board {
+        let mut board = Board::new();
+        for _ in 0..times {
+            for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                let m = Move::from_san(&board, san).unwrap();
+                board = board.move_piece(m).unwrap();
+            }
+        }
+        board
+    }
+
+    fn fen(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn test_draw_classification() {
+        for (board, reason, claimable) in [
+            (
+                fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+                DrawReason::Stalemate,
+                false,
+            ),
+            (
+                fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+                DrawReason::InsufficientMaterial,
+                false,
+            ),
+            (
+                fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80"),
+                DrawReason::FiftyMoveRule,
+                true,
+            ),
+            (
+                fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 80"),
+                DrawReason::SeventyFiveMoveRule,
+                false,
+            ),
+            (shuffle_knights(2), DrawReason::ThreefoldRepetition, true),
+            (shuffle_knights(4), DrawReason::FivefoldRepetition, false),
+        ] {
+            let status = board.game_status();
+            assert_eq!(GameStatus::Draw { reason, claimable }, status, "{}", reason);
+            assert_eq!(GameStatus::draw(reason), status, "{}", reason);
+            assert_eq!(!claimable, status.is_over(), "{}", reason);
+        }
+    }
+
+    #[test]
+    fn test_draw_display() {
+        assert_eq!(
+            "Stalemate! The game is a draw.",
+            GameStatus::draw(DrawReason::Stalemate).to_string()
+        );
+        assert_eq!(
+            "A draw can be claimed by threefold repetition.",
+            GameStatus::draw(DrawReason::ThreefoldRepetition).to_string()
+        );
+        assert_eq!(
+            "The game is a draw by the seventy-five-move rule.",
+            GameStatus::draw(DrawReason::SeventyFiveMoveRule).to_string()
+        );
+    }
+}
@@ -1,2 +1,6 @@
+pub mod ai;
+pub mod book;
+pub mod bot;
 pub mod cli;
 pub mod game;
+pub mod uci;
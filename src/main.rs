@@ -1,14 +1,105 @@
-mod cli;
-mod game;
+use std::{fs, process};
+
+use chess_rust::{book::Book, bot, cli, game};
+
+use cli::Command;
+
+use game::piece::piece_info::PieceColor;
+use game::status::GameStatus;
+use game::Game;
+use log::LevelFilter;
+use simple_logger::SimpleLogger;
 
 fn main() {
-    let mut game = game::board::Board::new();
+    // Quiet unless asked otherwise, i.e. RUST_LOG=trace for move checking details
+    SimpleLogger::new()
+        .with_level(LevelFilter::Warn)
+        .env()
+        .init()
+        .expect("Failed to initialize logger");
+
+    // --black-bot hands black's moves to a bot that plays at random, or from the Polyglot
+    // opening book given with --book <path> while the game is still in it. --moves-file
+    // <path> plays the moves listed in a file before handing over to the players.
+    let args: Vec<String> = std::env::args().collect();
+    let black_bot = args.iter().any(|arg| arg == "--black-bot");
+    let book = args
+        .iter()
+        .position(|arg| arg == "--book")
+        .and_then(|i| args.get(i + 1))
+        .map(|path| Book::load(path).expect("Failed to load opening book"));
+    let mut rng = rand::thread_rng();
+
+    let mut game = Game::new();
+    if let Some(path) = args
+        .iter()
+        .position(|arg| arg == "--moves-file")
+        .and_then(|i| args.get(i + 1))
+    {
+        let script = fs::read_to_string(path).expect("Failed to read moves file");
+        if let Err(e) = cli::play_moves_script(&mut game, &script) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        println!("\n{}", game.board().to_string_oriented(game.current_turn()));
+    }
 
     loop {
-        if let Some(new_move) = cli::prompt_make_move(&game) {
-            game = game.move_piece(new_move);
+        if black_bot && game.current_turn() == PieceColor::Black {
+            let bot_move = book
+                .as_ref()
+                .and_then(|book| book.probe(game.board()))
+                .or_else(|| bot::random_move(game.board(), &mut rng));
+            if let Some(bot_move) = bot_move {
+                println!("Black plays {}", bot_move.to_san(game.board()));
+                game.try_move(bot_move)
+                    .expect("Bot should only pick legal moves");
+            }
+        } else if let Some(command) = cli::prompt_command(game.board()) {
+            let result = match command {
+                Command::Move(new_move) => game.try_move(new_move),
+                Command::Resign => game.resign(game.current_turn()),
+                Command::OfferDraw => game.offer_draw().map(|_| {
+                    println!("{} offers a draw.", game.current_turn());
+                }),
+                Command::AcceptDraw => game.accept_draw(),
+                Command::ClaimDraw => game.claim_draw(),
+                Command::Undo => {
+                    take_back(&mut game);
+                    // Against the bot, the player's own last move goes back along with
+                    // the bot's reply, or the bot would only play it again
+                    if black_bot && game.current_turn() == PieceColor::Black {
+                        take_back(&mut game);
+                    }
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                println!("{}", e);
+            }
         }
 
-        println!("\n{game}");
+        // Show the board from the side of the player about to move
+        println!("\n{}", game.board().to_string_oriented(game.current_turn()));
+
+        let status = game.status();
+        if status.is_over() {
+            println!("\n{status}");
+            break;
+        }
+        if status != GameStatus::Ongoing {
+            // A draw the player to move may claim, or play on past
+            println!("{status}");
+        }
+        if game.board().is_in_check(game.current_turn()) {
+            println!("{} is in check!", game.current_turn());
+        }
+    }
+}
+
+fn take_back(game: &mut Game) {
+    match game.undo_move() {
+        Some(m) => println!("Took back {}.", m.to_san(game.board())),
+        None => println!("Nothing to undo."),
     }
 }
@@ -0,0 +1,167 @@
+use std::io::{self, BufRead, Write};
+
+use log::warn;
+
+use crate::ai;
+use crate::book::Book;
+use crate::game::board::Board;
+use crate::game::moves::Move;
+
+// How far to search when a GUI sends a bare `go` without a depth
+const DEFAULT_DEPTH: u8 = 3;
+
+/// Speaks the Universal Chess Interface over the given streams until told to `quit` or
+/// the input ends, so the engine can be driven by GUIs like Arena or Cute Chess.
+///
+/// Only the core of the protocol is supported: the `uci`/`isready` handshake,
+/// `ucinewgame`, `position` from the start position or a FEN with any moves after it, and
+/// `go depth N`. Anything else is ignored, as the protocol asks.
+pub fn run(input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+    run_with_book(input, output, None)
+}
+
+/// Runs the UCI loop like `run`, answering `go` from the opening book while the position
+/// is still in it and only searching once it runs out.
+pub fn run_with_book(
+    input: impl BufRead,
+    output: &mut impl Write,
+    book: Option<&Book>,
+) -> io::Result<()> {
+    let mut board = Board::new();
+
+    for line in input.lines() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        match tokens.first().copied() {
+            Some("uci") => {
+                writeln!(output, "id name chess_rust")?;
+                writeln!(output, "id author TLouison")?;
+                writeln!(output, "uciok")?;
+            }
+            Some("isready") => writeln!(output, "readyok")?,
+            Some("ucinewgame") => board = Board::new(),
+            Some("position") => match parse_position(&tokens[1..]) {
+                Some(position) => board = position,
+                None => warn!("Ignoring invalid position command: {}", line),
+            },
+            Some("go") => {
+                let depth = tokens
+                    .iter()
+                    .position(|&token| token == "depth")
+                    .and_then(|i| tokens.get(i + 1))
+                    .and_then(|depth| depth.parse().ok())
+                    .unwrap_or(DEFAULT_DEPTH);
+
+                // UCI's null move stands in when there is nothing left to play
+                let best = book
+                    .and_then(|book| book.probe(&board))
+                    .or_else(|| ai::best_move(&board, depth))
+                    .map_or(String::from("0000"), |m| m.to_uci());
+                writeln!(output, "bestmove {}", best)?;
+            }
+            Some("quit") => break,
+            _ => (),
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+// Handles the arguments of `position`: either `startpos` or `fen` and its six fields,
+// optionally followed by `moves` and the moves played since
+fn parse_position(args: &[&str]) -> Option<Board> {
+    let (mut board, rest) = match args.first().copied() {
+        Some("startpos") => (Board::new(), &args[1..]),
+        Some("fen") if args.len() >= 7 => {
+            (Board::from_fen(&args[1..7].join(" ")).ok()?, &args[7..])
+        }
+        _ => return None,
+    };
+
+    if let Some((&"moves", moves)) = rest.split_first() {
+        for notation in moves {
+            let m = Move::from_uci(&board, notation).ok()?;
+            board = board.move_piece(m).ok()?;
+        }
+    }
+    Some(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book::polyglot_key;
+    use crate::game::piece::piece_info::{PieceLoc, PieceType};
+
+    fn run_script(script: &str) -> String {
+        let mut output = Vec::new();
+        run(script.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_handshake() {
+        let output = run_script("uci\nisready\nquit\n");
+
+        assert!(output.contains("id name chess_rust\n"));
+        assert!(output.ends_with("uciok\nreadyok\n"));
+    }
+
+    #[test]
+    fn test_go_replies_with_legal_bestmove() {
+        let output = run_script("ucinewgame\nposition startpos moves e2e4 e7e5 g1f3\ngo depth 2\n");
+        let reply = output.trim().strip_prefix("bestmove ").unwrap();
+
+        let board = parse_position(&["startpos", "moves", "e2e4", "e7e5", "g1f3"]).unwrap();
+        assert!(Move::from_uci(&board, reply).is_ok());
+    }
+
+    #[test]
+    fn test_go_finds_mate_from_fen() {
+        let output = run_script("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 2\n");
+
+        assert_eq!("bestmove a1a8\n", output);
+    }
+
+    #[test]
+    fn test_position_with_promotion() {
+        let board = parse_position(&[
+            "fen",
+            "8/P6k/8/8/8/8/8/K7",
+            "w",
+            "-",
+            "-",
+            "0",
+            "1",
+            "moves",
+            "a7a8n",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Some(PieceType::Knight),
+            board
+                .get_piece_at_location(PieceLoc::new(7, 0))
+                .map(|p| p.piece_type)
+        );
+    }
+
+    #[test]
+    fn test_go_plays_from_book() {
+        let mut bytes = polyglot_key(&Board::new()).to_be_bytes().to_vec();
+        // b1 to a3, with a weight of one
+        bytes.extend_from_slice(&[0x00, 0x50, 0x00, 0x01, 0, 0, 0, 0]);
+        let book = Book::from_bytes(&bytes).unwrap();
+
+        let mut output = Vec::new();
+        run_with_book("go depth 1\n".as_bytes(), &mut output, Some(&book)).unwrap();
+        assert_eq!("bestmove b1a3\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_position_is_ignored() {
+        assert!(parse_position(&["startpos", "moves", "e2e5"]).is_none());
+        assert!(parse_position(&["fen", "8/8/8"]).is_none());
+    }
+}
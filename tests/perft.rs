@@ -0,0 +1,101 @@
+use chess_rust::game::bitboard::BitBoard;
+use chess_rust::game::board::Board;
+
+#[test]
+fn perft_start_position() {
+    let board = Board::new();
+
+    assert_eq!(1, board.perft(0));
+    assert_eq!(20, board.perft(1));
+    assert_eq!(400, board.perft(2));
+    assert_eq!(8902, board.perft(3));
+}
+
+#[test]
+fn perft_divide_start_position() {
+    let board = Board::new();
+    let divide = board.perft_divide(3);
+
+    assert_eq!(20, divide.len());
+    assert!(divide.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(8902, divide.iter().map(|(_, nodes)| nodes).sum::<u64>());
+    assert!(divide.contains(&(String::from("Nc3"), 440)));
+    assert!(divide.contains(&(String::from("e4"), 600)));
+}
+
+// Each promotion counts as its own move, for all four pieces a pawn can become
+// Positions 4 and 5 further down cover promotions on a crowded board
+#[test]
+fn perft_promotions() {
+    // A lone pawn one step from queening, with the kings out of the way
+    let board = Board::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap();
+    assert_eq!(4 + 3, board.perft(1));
+    let divide = board.perft_divide(1);
+    for san in ["a8=Q+", "a8=R+", "a8=B", "a8=N"] {
+        assert!(divide.iter().any(|(m, _)| m == san), "{} is missing", san);
+    }
+}
+
+// benches/movegen.rs times the two backends against each other
+#[test]
+fn perft_bitboard_matches_board() {
+    let kiwipete =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+    for (board, depth) in [(Board::new(), 3), (kiwipete, 2)] {
+        let bitboard = BitBoard::from_board(&board).unwrap();
+        assert_eq!(board.perft(depth), bitboard.perft(depth), "perft({depth})");
+    }
+}
+
+// The standard positions from the Chess Programming Wiki, with their published counts
+#[test]
+fn perft_kiwipete() {
+    // Castling both ways for both sides, pins, en passant and discovered checks
+    let board =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+    for (depth, nodes) in [(1, 48), (2, 2039), (3, 97862)] {
+        assert_eq!(nodes, board.perft(depth), "perft({depth})");
+    }
+}
+
+#[test]
+fn perft_position_3() {
+    // A sparse endgame full of en passant captures that would expose the king to a rook
+    let board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+
+    for (depth, nodes) in [(1, 14), (2, 191), (3, 2812), (4, 43238), (5, 674624)] {
+        assert_eq!(nodes, board.perft(depth), "perft({depth})");
+    }
+}
+
+#[test]
+fn perft_position_4() {
+    // Black can promote on b1 by pushing or capturing
+    let board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+        .unwrap();
+    let mirrored =
+        Board::from_fen("r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1")
+            .unwrap();
+
+    // The mirrored position swaps the colors, so both sides must count the same
+    for board in [board, mirrored] {
+        for (depth, nodes) in [(1, 6), (2, 264), (3, 9467)] {
+            assert_eq!(nodes, board.perft(depth), "perft({depth})");
+        }
+    }
+}
+
+#[test]
+fn perft_position_5() {
+    // White can promote on d8 by pushing, or by capturing on c8
+    let board =
+        Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+
+    for (depth, nodes) in [(1, 44), (2, 1486), (3, 62379)] {
+        assert_eq!(nodes, board.perft(depth), "perft({depth})");
+    }
+}
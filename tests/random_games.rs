@@ -0,0 +1,49 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use chess_rust::bot;
+use chess_rust::game::board::Board;
+use chess_rust::game::status::GameStatus;
+
+const GAMES: u64 = 20;
+
+// Plays one random game from the start until it is over, recording each move in UCI form
+// so a failing game can be replayed with `position startpos moves ...`
+fn play_random_game(seed: u64, history: &mut Vec<String>) -> GameStatus {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut board = Board::new();
+
+    loop {
+        let status = board.game_status();
+        if status.is_over() {
+            return status;
+        }
+
+        let m = bot::random_move(&board, &mut rng).expect("An ongoing game has a legal move");
+        history.push(m.to_uci());
+        board = board.move_piece(m).expect("Random moves should be legal");
+
+        let fen = board.to_fen();
+        let reloaded = Board::from_fen(&fen).expect("Generated FEN should parse");
+        assert_eq!(fen, reloaded.to_fen(), "FEN did not round-trip");
+        assert!(
+            board.same_position(&reloaded),
+            "FEN lost part of the position"
+        );
+    }
+}
+
+#[test]
+fn random_games_play_to_completion() {
+    for seed in 0..GAMES {
+        let mut history = Vec::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| play_random_game(seed, &mut history)));
+
+        match result {
+            Ok(status) => println!("Game {seed} ended after {} moves: {status}", history.len()),
+            Err(_) => panic!("Game {seed} failed after the moves: {}", history.join(" ")),
+        }
+    }
+}